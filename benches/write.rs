@@ -0,0 +1,52 @@
+use chrono::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use icalendar::*;
+
+use std::io::{self, Write};
+
+/// A feed of `count` weekly meetings with long, escaped descriptions that need folding
+fn feed(count: usize) -> Calendar {
+    let start = Utc.with_ymd_and_hms(2021, 3, 1, 9, 0, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            let starts = start + Duration::days(i as i64);
+            Event::new()
+                .uid(&format!("meeting-{}@example.com", i))
+                .add_property("DTSTAMP", "20210301T000000Z")
+                .summary(&format!("Meeting #{}; room 1, floor 2", i))
+                .description(&"Agenda: budget, hiring; roadmap. Bring notes.\n".repeat(8))
+                .location("Room 1, Floor 2")
+                .starts(starts)
+                .ends(starts + Duration::hours(1))
+                .attendee(
+                    Attendee::new("jane@example.com")
+                        .common_name("Jane Doe")
+                        .done(),
+                )
+                .done()
+        })
+        .collect()
+}
+
+fn write(c: &mut Criterion) {
+    let calendar = feed(1000);
+    let size = calendar.to_string().len() as u64;
+
+    let mut group = c.benchmark_group("write");
+    group.throughput(Throughput::Bytes(size));
+    group.bench_function("to_string", |b| {
+        b.iter(|| io::sink().write_all(calendar.to_string().as_bytes()))
+    });
+    group.bench_function("write_to", |b| b.iter(|| calendar.write_to(io::sink())));
+    group.bench_function("write_to_vec", |b| {
+        b.iter_batched_ref(
+            || Vec::with_capacity(size as usize),
+            |out| calendar.write_to(out),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, write);
+criterion_main!(benches);
@@ -1,6 +1,7 @@
 use chrono::*;
 use icalendar::*;
 
+#[allow(deprecated)]
 fn main() {
     let event = Event::new()
         .summary("test event")
@@ -1,14 +1,21 @@
 use chrono::*;
 use icalendar::*;
 
+#[allow(deprecated)]
 fn main() {
     let event = Event::new()
         .summary("test event")
         .description("here I have something really important to do")
         .starts(Utc::now())
         .class(Class::Confidential)
-        //.repeats_every(15.days())
-        //.repeats(Every::second().wednesday())
+        .repeats(
+            RecurrenceRule::new(Freq::Weekly)
+                .interval(2)
+                .by_day(vec![Weekday::Wed])
+                .count(5)
+                .done()
+                .unwrap(),
+        )
         .ends(Utc::now() + Duration::days(1))
         //.all_day()
         .append_property(
@@ -0,0 +1,272 @@
+use chrono::{DateTime, Duration, Utc};
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::components::{component_impl, Component, InnerComponent};
+use crate::output::WriteOptions;
+#[cfg(any(feature = "jcal", feature = "xcal"))]
+use crate::parser::RawComponent;
+use crate::properties::{Parameter, Property};
+use crate::property_map::PropertyMap;
+
+/// VALARM [(RFC 5545, Section 3.6.6 )](https://tools.ietf.org/html/rfc5545#section-3.6.6)
+///
+/// A reminder that is attached to an [`Event`](crate::Event) or a [`Todo`](crate::Todo) with
+/// their `alarm` methods.
+///
+/// ```rust
+/// # use chrono::Duration;
+/// # use icalendar::*;
+/// let event = Event::new()
+///     .summary("Dentist")
+///     .alarm(
+///         Alarm::display("Leave for the dentist", -Duration::minutes(30))
+///             .repeat(2, Duration::minutes(5))
+///             .done(),
+///     )
+///     .done();
+/// assert_eq!(event.alarms().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Alarm {
+    inner: InnerComponent,
+}
+
+/// Defines what happens when an [`Alarm`] is triggered
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum Action {
+    /// Plays a sound
+    Audio,
+    /// Shows the DESCRIPTION to the user
+    Display,
+    /// Sends the DESCRIPTION as an email with the SUMMARY as subject to every ATTENDEE
+    Email,
+}
+
+impl From<Action> for Property {
+    fn from(val: Action) -> Self {
+        Property::new(
+            "ACTION",
+            match val {
+                Action::Audio => "AUDIO",
+                Action::Display => "DISPLAY",
+                Action::Email => "EMAIL",
+            },
+        )
+    }
+}
+
+/// The end of a component that a relative [`Trigger`] refers to
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum Related {
+    /// DTSTART
+    #[default]
+    Start,
+    /// DTEND of an `Event`, or DUE of a `Todo`
+    End,
+}
+
+/// When an [`Alarm`] is triggered
+///
+/// Durations convert to triggers relative to the start, negative ones fire before it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Trigger {
+    /// Relative to the start or end of the component
+    Relative {
+        /// Time from the related point in time on
+        duration: Duration,
+        /// Whether `duration` is counted from the start or the end
+        related: Related,
+    },
+    /// At a fixed point in time
+    Absolute(DateTime<Utc>),
+}
+
+/// Converts to a trigger relative to the start.
+impl From<Duration> for Trigger {
+    fn from(duration: Duration) -> Self {
+        Trigger::Relative {
+            duration,
+            related: Related::Start,
+        }
+    }
+}
+
+impl From<(Duration, Related)> for Trigger {
+    fn from((duration, related): (Duration, Related)) -> Self {
+        Trigger::Relative { duration, related }
+    }
+}
+
+impl From<DateTime<Utc>> for Trigger {
+    fn from(date_time: DateTime<Utc>) -> Self {
+        Trigger::Absolute(date_time)
+    }
+}
+
+impl From<Trigger> for Property {
+    fn from(val: Trigger) -> Self {
+        match val {
+            Trigger::Relative { duration, related } => {
+                let mut property = Property::with_value("TRIGGER", duration);
+                if related == Related::End {
+                    property.append_parameter(Parameter::new("RELATED", "END"));
+                }
+                property
+            }
+            Trigger::Absolute(date_time) => Property::with_value("TRIGGER", date_time),
+        }
+    }
+}
+
+impl Alarm {
+    /// Creates a new Alarm with the given action and trigger.
+    ///
+    /// Prefer [`display`](Alarm::display), [`audio`](Alarm::audio) or
+    /// [`email`](Alarm::email), which also set the properties the action requires.
+    pub fn new<T: Into<Trigger>>(action: Action, trigger: T) -> Self {
+        let mut alarm = Alarm::default();
+        alarm.action(action).trigger(trigger);
+        alarm
+    }
+
+    /// Creates an Alarm that shows `description`
+    pub fn display<T: Into<Trigger>>(description: &str, trigger: T) -> Self {
+        let mut alarm = Alarm::new(Action::Display, trigger);
+        alarm.description(description);
+        alarm
+    }
+
+    /// Creates an Alarm that plays a sound
+    ///
+    /// Which sound is up to the client, unless one is [attached](Alarm::attach).
+    pub fn audio<T: Into<Trigger>>(trigger: T) -> Self {
+        Alarm::new(Action::Audio, trigger)
+    }
+
+    /// Creates an Alarm that sends an email
+    ///
+    /// Recipients are added with [`attendee`](Component::attendee).
+    pub fn email<T: Into<Trigger>>(summary: &str, description: &str, trigger: T) -> Self {
+        let mut alarm = Alarm::new(Action::Email, trigger);
+        alarm.summary(summary).description(description);
+        alarm
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        Alarm {
+            inner: self.inner.done(),
+        }
+    }
+
+    /// Set the ACTION `Property`
+    pub fn action(&mut self, action: Action) -> &mut Self {
+        self.append_property(action.into())
+    }
+
+    /// Set the TRIGGER `Property`
+    pub fn trigger<T: Into<Trigger>>(&mut self, trigger: T) -> &mut Self {
+        self.append_property(trigger.into().into())
+    }
+
+    /// Repeats the alarm `count` more times after it was triggered, every `interval`
+    ///
+    /// Sets the REPEAT and DURATION `Property`s, which only occur together.
+    /// A `count` beyond what an INTEGER value holds is capped at `i32::MAX`.
+    pub fn repeat(&mut self, count: u32, interval: Duration) -> &mut Self {
+        let count = i32::try_from(count).unwrap_or(i32::MAX);
+        self.append_property(Property::with_value("REPEAT", count))
+            .append_property(Property::with_value("DURATION", interval))
+    }
+
+    /// Adds an ATTACH `Property`, the sound of an audio alarm or an attachment of an email
+    pub fn attach(&mut self, uri: &str) -> &mut Self {
+        self.append_property(Property::new("ATTACH", uri))
+    }
+
+    /// The `Alarm` as a `RawComponent`
+    #[cfg(any(feature = "jcal", feature = "xcal"))]
+    pub(crate) fn to_raw(&self) -> RawComponent {
+        self.inner.to_raw("VALARM")
+    }
+}
+
+component_impl! { Alarm, "VALARM", {
+    /// Writes `Alarm` into a `Writer` using `std::fmt`, without DTSTAMP and UID as alarms have
+    /// none.
+    fn fmt_write_with<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        _options: &WriteOptions,
+    ) -> Result<(), fmt::Error> {
+        write_crlf!(out, "BEGIN:VALARM")?;
+        self.inner.fmt_write_properties(out)?;
+        write_crlf!(out, "END:VALARM")?;
+        Ok(())
+    }
+} }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attendee::Attendee;
+    use chrono::TimeZone;
+
+    #[test]
+    fn repeat_is_capped() {
+        let alarm = Alarm::display("Wake up", -Duration::minutes(10))
+            .repeat(u32::MAX, Duration::minutes(5))
+            .done();
+        assert_eq!(alarm.properties()["REPEAT"].value(), "2147483647");
+    }
+
+    #[test]
+    fn display_alarm() {
+        let alarm = Alarm::display("Wake up", (-Duration::minutes(10), Related::End))
+            .repeat(3, Duration::minutes(5))
+            .done();
+        assert_eq!(
+            alarm.to_string(),
+            "BEGIN:VALARM\r\n\
+             ACTION:DISPLAY\r\n\
+             DESCRIPTION:Wake up\r\n\
+             DURATION:PT5M\r\n\
+             REPEAT:3\r\n\
+             TRIGGER;RELATED=END:-PT10M\r\n\
+             END:VALARM\r\n"
+        );
+
+        let mut alarm = alarm;
+        alarm
+            .property_mut("DESCRIPTION")
+            .unwrap()
+            .set_value("Get up");
+        assert!(alarm.remove_property("REPEAT").is_some());
+        assert_eq!(alarm.properties()["DESCRIPTION"].value(), "Get up");
+    }
+
+    #[test]
+    fn email_alarm_with_absolute_trigger() {
+        let at = Utc.with_ymd_and_hms(2021, 3, 1, 8, 0, 0).unwrap();
+        let alarm = Alarm::email("Reminder", "Meeting at 9", at)
+            .attendee(Attendee::new("john@example.com"))
+            .done();
+        assert_eq!(
+            alarm.to_string(),
+            "BEGIN:VALARM\r\n\
+             ACTION:EMAIL\r\n\
+             ATTENDEE:mailto:john@example.com\r\n\
+             DESCRIPTION:Meeting at 9\r\n\
+             SUMMARY:Reminder\r\n\
+             TRIGGER;VALUE=DATE-TIME:20210301T080000Z\r\n\
+             END:VALARM\r\n"
+        );
+    }
+}
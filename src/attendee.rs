@@ -0,0 +1,524 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+use crate::parser::ParseError;
+use crate::properties::{Parameter, Property};
+
+/// Participation role of an [`Attendee`] [(RFC 5545, Section 3.2.16 )](https://tools.ietf.org/html/rfc5545#section-3.2.16)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum AttendeeRole {
+    /// CHAIR, leads the meeting
+    Chair,
+    /// REQ-PARTICIPANT, the default
+    #[default]
+    ReqParticipant,
+    /// OPT-PARTICIPANT
+    OptParticipant,
+    /// NON-PARTICIPANT, e.g. only copied for information
+    NonParticipant,
+    /// Any other value, an experimental `X-` name or one registered with IANA
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String),
+}
+
+/// Participation status of an [`Attendee`] [(RFC 5545, Section 3.2.12 )](https://tools.ietf.org/html/rfc5545#section-3.2.12)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum ParticipationStatus {
+    /// NEEDS-ACTION, the default
+    #[default]
+    NeedsAction,
+    /// ACCEPTED
+    Accepted,
+    /// DECLINED
+    Declined,
+    /// TENTATIVE, only for events
+    Tentative,
+    /// DELEGATED to the attendees in DELEGATED-TO
+    Delegated,
+    /// COMPLETED, only for todos
+    Completed,
+    /// IN-PROCESS, only for todos
+    InProcess,
+    /// Any other value, an experimental `X-` name or one registered with IANA
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String),
+}
+
+/// Kind of calendar user [(RFC 5545, Section 3.2.3 )](https://tools.ietf.org/html/rfc5545#section-3.2.3)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum CalendarUserType {
+    /// INDIVIDUAL, the default
+    #[default]
+    Individual,
+    /// GROUP of individuals
+    Group,
+    /// RESOURCE, such as a projector
+    Resource,
+    /// ROOM
+    Room,
+    /// UNKNOWN
+    Unknown,
+    /// Any other value, an experimental `X-` name or one registered with IANA
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String),
+}
+
+/// Whether `value` is an `x-name` or `iana-token`, made up of letters, digits and `-`
+fn is_token(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+macro_rules! parameter_enum {
+    ($t:ident, $key:expr, $($variant:path => $value:expr),+ $(,)?) => {
+        impl $t {
+            fn as_str(&self) -> &str {
+                match self {
+                    $($variant => $value,)+
+                    $t::Other(other) => other,
+                }
+            }
+        }
+
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $t {
+            type Err = ParseError;
+
+            /// Reads a value case-insensitively, unknown tokens become `Other`
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_uppercase().as_str() {
+                    $($value => Ok($variant),)+
+                    _ if is_token(s) => Ok($t::Other(s.to_owned())),
+                    _ => Err(ParseError::new(format!("invalid {} {:?}", $key, s))),
+                }
+            }
+        }
+
+        impl From<$t> for Parameter {
+            fn from(val: $t) -> Self {
+                Parameter::new($key, val.as_str())
+            }
+        }
+    };
+}
+
+parameter_enum! { AttendeeRole, "ROLE",
+    AttendeeRole::Chair => "CHAIR",
+    AttendeeRole::ReqParticipant => "REQ-PARTICIPANT",
+    AttendeeRole::OptParticipant => "OPT-PARTICIPANT",
+    AttendeeRole::NonParticipant => "NON-PARTICIPANT",
+}
+
+parameter_enum! { ParticipationStatus, "PARTSTAT",
+    ParticipationStatus::NeedsAction => "NEEDS-ACTION",
+    ParticipationStatus::Accepted => "ACCEPTED",
+    ParticipationStatus::Declined => "DECLINED",
+    ParticipationStatus::Tentative => "TENTATIVE",
+    ParticipationStatus::Delegated => "DELEGATED",
+    ParticipationStatus::Completed => "COMPLETED",
+    ParticipationStatus::InProcess => "IN-PROCESS",
+}
+
+parameter_enum! { CalendarUserType, "CUTYPE",
+    CalendarUserType::Individual => "INDIVIDUAL",
+    CalendarUserType::Group => "GROUP",
+    CalendarUserType::Resource => "RESOURCE",
+    CalendarUserType::Room => "ROOM",
+    CalendarUserType::Unknown => "UNKNOWN",
+}
+
+/// Turns an email address into a `CAL-ADDRESS`, other URIs are kept as they are.
+pub(crate) fn cal_address(address: &str) -> String {
+    if address.contains(':') {
+        address.to_owned()
+    } else {
+        format!("mailto:{}", address)
+    }
+}
+
+/// Splits a parameter value holding a list of `CAL-ADDRESS`es
+fn address_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter(|address| !address.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Somebody taking part in an event or todo, the ATTENDEE `Property`
+///
+/// Addresses without a URI scheme are taken as email addresses and written as `mailto:` URIs.
+///
+/// ```rust
+/// # use icalendar::*;
+/// let event = Event::new()
+///     .summary("Planning")
+///     .organizer(Organizer::new("boss@example.com").common_name("The Boss").done())
+///     .attendee(
+///         Attendee::new("jane@example.com")
+///             .common_name("Jane Doe")
+///             .role(AttendeeRole::Chair)
+///             .rsvp(true)
+///             .done(),
+///     )
+///     .done();
+/// assert_eq!(event.attendees()[0].address, "mailto:jane@example.com");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attendee {
+    /// The `CAL-ADDRESS`, e.g. `mailto:jane@example.com`
+    pub address: String,
+    /// The display name, `CN`
+    pub common_name: Option<String>,
+    /// The `ROLE`, required participation if not given
+    pub role: Option<AttendeeRole>,
+    /// The `PARTSTAT`, needs action if not given
+    pub participation_status: Option<ParticipationStatus>,
+    /// Whether a reply is expected, `RSVP`
+    pub rsvp: Option<bool>,
+    /// The `CUTYPE`, an individual if not given
+    pub user_type: Option<CalendarUserType>,
+    /// Addresses this attendee delegated to, `DELEGATED-TO`
+    pub delegated_to: Vec<String>,
+    /// Addresses this attendee was delegated from, `DELEGATED-FROM`
+    pub delegated_from: Vec<String>,
+    /// Address of who acts on behalf of this attendee, `SENT-BY`
+    pub sent_by: Option<String>,
+    /// URI of a directory entry, `DIR`
+    pub directory: Option<String>,
+}
+
+impl Attendee {
+    /// Creates a new Attendee from an email address or `CAL-ADDRESS` URI.
+    pub fn new(address: &str) -> Self {
+        Attendee {
+            address: cal_address(address),
+            ..Default::default()
+        }
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        mem::take(self)
+    }
+
+    /// Set the display name, `CN`
+    pub fn common_name(&mut self, name: &str) -> &mut Self {
+        self.common_name = Some(name.to_owned());
+        self
+    }
+
+    /// Set the `ROLE`
+    pub fn role(&mut self, role: AttendeeRole) -> &mut Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// Set the participation status, `PARTSTAT`
+    pub fn participation_status(&mut self, status: ParticipationStatus) -> &mut Self {
+        self.participation_status = Some(status);
+        self
+    }
+
+    /// Set whether a reply is expected, `RSVP`
+    pub fn rsvp(&mut self, rsvp: bool) -> &mut Self {
+        self.rsvp = Some(rsvp);
+        self
+    }
+
+    /// Set the kind of calendar user, `CUTYPE`
+    pub fn user_type(&mut self, user_type: CalendarUserType) -> &mut Self {
+        self.user_type = Some(user_type);
+        self
+    }
+
+    /// Adds an address this attendee delegated to, `DELEGATED-TO`
+    pub fn delegated_to(&mut self, address: &str) -> &mut Self {
+        self.delegated_to.push(cal_address(address));
+        self
+    }
+
+    /// Adds an address this attendee was delegated from, `DELEGATED-FROM`
+    pub fn delegated_from(&mut self, address: &str) -> &mut Self {
+        self.delegated_from.push(cal_address(address));
+        self
+    }
+
+    /// Set the address of who acts on behalf of this attendee, `SENT-BY`
+    pub fn sent_by(&mut self, address: &str) -> &mut Self {
+        self.sent_by = Some(cal_address(address));
+        self
+    }
+
+    /// Set the URI of a directory entry, `DIR`, e.g. an LDAP URI
+    pub fn directory(&mut self, uri: &str) -> &mut Self {
+        self.directory = Some(uri.to_owned());
+        self
+    }
+}
+
+impl From<Attendee> for Property {
+    fn from(val: Attendee) -> Self {
+        let mut property = Property::new("ATTENDEE", &val.address);
+        if let Some(name) = val.common_name {
+            property.add_parameter("CN", &name);
+        }
+        if let Some(role) = val.role {
+            property.append_parameter(role);
+        }
+        if let Some(status) = val.participation_status {
+            property.append_parameter(status);
+        }
+        if let Some(rsvp) = val.rsvp {
+            property.add_parameter("RSVP", if rsvp { "TRUE" } else { "FALSE" });
+        }
+        if let Some(user_type) = val.user_type {
+            property.append_parameter(user_type);
+        }
+        if !val.delegated_to.is_empty() {
+            property.add_parameter("DELEGATED-TO", &val.delegated_to.join(","));
+        }
+        if !val.delegated_from.is_empty() {
+            property.add_parameter("DELEGATED-FROM", &val.delegated_from.join(","));
+        }
+        if let Some(sent_by) = val.sent_by {
+            property.add_parameter("SENT-BY", &sent_by);
+        }
+        if let Some(directory) = val.directory {
+            property.add_parameter("DIR", &directory);
+        }
+        property
+    }
+}
+
+impl TryFrom<&Property> for Attendee {
+    type Error = ParseError;
+
+    /// Reads an ATTENDEE `Property`, unknown parameter values are an error.
+    fn try_from(property: &Property) -> Result<Self, Self::Error> {
+        if property.key() != "ATTENDEE" {
+            return Err(ParseError::new(format!(
+                "expected ATTENDEE, got {}",
+                property.key()
+            )));
+        }
+        let parameter = |key| property.parameter(key).map(Parameter::value);
+        let owned = |key| parameter(key).map(str::to_owned);
+        Ok(Attendee {
+            address: property.value().into_owned(),
+            common_name: owned("CN"),
+            role: parameter("ROLE").map(str::parse).transpose()?,
+            participation_status: parameter("PARTSTAT").map(str::parse).transpose()?,
+            rsvp: match parameter("RSVP").map(str::to_uppercase).as_deref() {
+                None => None,
+                Some("TRUE") => Some(true),
+                Some("FALSE") => Some(false),
+                Some(other) => return Err(ParseError::new(format!("invalid RSVP {:?}", other))),
+            },
+            user_type: parameter("CUTYPE").map(str::parse).transpose()?,
+            delegated_to: parameter("DELEGATED-TO")
+                .map(address_list)
+                .unwrap_or_default(),
+            delegated_from: parameter("DELEGATED-FROM")
+                .map(address_list)
+                .unwrap_or_default(),
+            sent_by: owned("SENT-BY"),
+            directory: owned("DIR"),
+        })
+    }
+}
+
+/// Who organizes an event or todo, the ORGANIZER `Property`
+///
+/// Addresses without a URI scheme are taken as email addresses and written as `mailto:` URIs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Organizer {
+    /// The `CAL-ADDRESS`, e.g. `mailto:boss@example.com`
+    pub address: String,
+    /// The display name, `CN`
+    pub common_name: Option<String>,
+    /// Address of who acts on behalf of the organizer, `SENT-BY`
+    pub sent_by: Option<String>,
+    /// URI of a directory entry, `DIR`
+    pub directory: Option<String>,
+}
+
+impl Organizer {
+    /// Creates a new Organizer from an email address or `CAL-ADDRESS` URI.
+    pub fn new(address: &str) -> Self {
+        Organizer {
+            address: cal_address(address),
+            ..Default::default()
+        }
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        mem::take(self)
+    }
+
+    /// Set the display name, `CN`
+    pub fn common_name(&mut self, name: &str) -> &mut Self {
+        self.common_name = Some(name.to_owned());
+        self
+    }
+
+    /// Set the address of who acts on behalf of the organizer, `SENT-BY`
+    pub fn sent_by(&mut self, address: &str) -> &mut Self {
+        self.sent_by = Some(cal_address(address));
+        self
+    }
+
+    /// Set the URI of a directory entry, `DIR`, e.g. an LDAP URI
+    pub fn directory(&mut self, uri: &str) -> &mut Self {
+        self.directory = Some(uri.to_owned());
+        self
+    }
+}
+
+impl From<Organizer> for Property {
+    fn from(val: Organizer) -> Self {
+        let mut property = Property::new("ORGANIZER", &val.address);
+        if let Some(name) = val.common_name {
+            property.add_parameter("CN", &name);
+        }
+        if let Some(sent_by) = val.sent_by {
+            property.add_parameter("SENT-BY", &sent_by);
+        }
+        if let Some(directory) = val.directory {
+            property.add_parameter("DIR", &directory);
+        }
+        property
+    }
+}
+
+impl TryFrom<&Property> for Organizer {
+    type Error = ParseError;
+
+    /// Reads an ORGANIZER `Property`
+    fn try_from(property: &Property) -> Result<Self, Self::Error> {
+        if property.key() != "ORGANIZER" {
+            return Err(ParseError::new(format!(
+                "expected ORGANIZER, got {}",
+                property.key()
+            )));
+        }
+        let owned = |key| {
+            property
+                .parameter(key)
+                .map(|parameter| parameter.value().to_owned())
+        };
+        Ok(Organizer {
+            address: property.value().into_owned(),
+            common_name: owned("CN"),
+            sent_by: owned("SENT-BY"),
+            directory: owned("DIR"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn attendee_parameters() {
+        let attendee = Attendee::new("jane@example.com")
+            .common_name("Doe, Jane")
+            .role(AttendeeRole::OptParticipant)
+            .participation_status(ParticipationStatus::Delegated)
+            .rsvp(false)
+            .user_type(CalendarUserType::Individual)
+            .delegated_to("john@example.com")
+            .delegated_to("mailto:joe@example.com")
+            .sent_by("assistant@example.com")
+            .directory("ldap://example.com:6666/o=ABC")
+            .done();
+        let property = Property::from(attendee.clone());
+        assert_eq!(property.value(), "mailto:jane@example.com");
+        let value = |key| property.parameter(key).unwrap().value();
+        assert_eq!(value("ROLE"), "OPT-PARTICIPANT");
+        assert_eq!(value("PARTSTAT"), "DELEGATED");
+        assert_eq!(value("RSVP"), "FALSE");
+        assert_eq!(value("CUTYPE"), "INDIVIDUAL");
+        assert_eq!(
+            value("DELEGATED-TO"),
+            "mailto:john@example.com,mailto:joe@example.com"
+        );
+        assert_eq!(value("SENT-BY"), "mailto:assistant@example.com");
+        assert_eq!(Attendee::try_from(&property), Ok(attendee));
+    }
+
+    #[test]
+    fn attendee_from_parsed_property() {
+        let property = Property::new("ATTENDEE", "mailto:jane@example.com")
+            .add_parameter("PARTSTAT", "accepted")
+            .add_parameter("SENT-BY", "mailto:assistant@example.com")
+            .done();
+        let attendee = Attendee::try_from(&property).unwrap();
+        assert_eq!(
+            attendee.participation_status,
+            Some(ParticipationStatus::Accepted)
+        );
+        assert_eq!(
+            attendee.sent_by.as_deref(),
+            Some("mailto:assistant@example.com")
+        );
+        assert_eq!(attendee.role, None);
+
+        let extended = Property::new("ATTENDEE", "mailto:jane@example.com")
+            .add_parameter("ROLE", "X-OBSERVER")
+            .add_parameter("PARTSTAT", "X-WAITING")
+            .add_parameter("CUTYPE", "X-BOT")
+            .done();
+        let attendee = Attendee::try_from(&extended).unwrap();
+        assert_eq!(
+            attendee.role,
+            Some(AttendeeRole::Other("X-OBSERVER".into()))
+        );
+        assert_eq!(
+            attendee.participation_status,
+            Some(ParticipationStatus::Other("X-WAITING".into()))
+        );
+        assert_eq!(
+            attendee.user_type,
+            Some(CalendarUserType::Other("X-BOT".into()))
+        );
+        let written = Property::from(attendee);
+        assert_eq!(written.parameter("PARTSTAT").unwrap().value(), "X-WAITING");
+
+        let invalid = Property::new("ATTENDEE", "mailto:jane@example.com")
+            .add_parameter("ROLE", "by stander")
+            .done();
+        assert!(Attendee::try_from(&invalid).is_err());
+    }
+
+    #[test]
+    fn organizer() {
+        let organizer = Organizer::new("boss@example.com")
+            .common_name("The Boss")
+            .done();
+        let property = Property::from(organizer.clone());
+        assert_eq!(property.key(), "ORGANIZER");
+        assert_eq!(property.value(), "mailto:boss@example.com");
+        assert_eq!(property.parameter("CN").unwrap().value(), "The Boss");
+        assert_eq!(Organizer::try_from(&property), Ok(organizer));
+    }
+}
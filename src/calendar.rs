@@ -1,43 +1,130 @@
 use crate::components::*;
+use crate::occurrences::date_times;
+use crate::output::WriteOptions;
+#[cfg(any(feature = "jcal", feature = "xcal"))]
+use crate::parser::RawComponent;
+use crate::period::{merge_periods, parse_duration, Period};
+#[cfg(feature = "chrono-tz")]
+use crate::repeats::{Limit, RecurrenceRule};
+use crate::timezone::Timezone;
 
-use crate::{Parameter, Property};
-use chrono::Duration;
+use crate::{FreeBusyType, Parameter, Property};
+#[cfg(feature = "chrono-tz")]
+use chrono::Offset;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use std::borrow::Cow;
+#[cfg(feature = "chrono-tz")]
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::convert::Into;
 use std::fmt;
+use std::io;
 use std::iter::FromIterator;
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut};
 
-#[derive(Debug)]
+/// The header properties written at the top of every calendar, with their defaults
+const HEADER_PROPERTIES: &[(&str, &str)] = &[
+    ("VERSION", "2.0"),
+    ("PRODID", "ICALENDAR-RS"),
+    ("CALSCALE", "GREGORIAN"),
+];
+
+/// Calendar properties that must not occur more than once
+const SINGLE_PROPERTIES: &[&str] = &["VERSION", "PRODID", "CALSCALE", "METHOD"];
+
+/// Any of the components a [`Calendar`] holds
+///
+/// Every component converts into this with `From`, so they can all be `push`ed.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalendarElement {
+    /// A VTODO
     Todo(Todo),
+    /// A VEVENT
     Event(Event),
+    /// A VJOURNAL
+    Journal(Journal),
+    /// A VFREEBUSY
+    FreeBusy(FreeBusy),
+    /// A VVENUE
     Venue(Venue),
+    /// A VTIMEZONE
+    Timezone(Timezone),
+}
+
+impl From<Event> for CalendarElement {
+    fn from(val: Event) -> Self {
+        CalendarElement::Event(val)
+    }
+}
+
+impl From<Todo> for CalendarElement {
+    fn from(val: Todo) -> Self {
+        CalendarElement::Todo(val)
+    }
 }
 
-impl Into<CalendarElement> for Event {
-    fn into(self) -> CalendarElement {
-        CalendarElement::Event(self)
+impl From<Journal> for CalendarElement {
+    fn from(val: Journal) -> Self {
+        CalendarElement::Journal(val)
     }
 }
 
-impl Into<CalendarElement> for Todo {
-    fn into(self) -> CalendarElement {
-        CalendarElement::Todo(self)
+impl From<FreeBusy> for CalendarElement {
+    fn from(val: FreeBusy) -> Self {
+        CalendarElement::FreeBusy(val)
     }
 }
 
-impl Into<CalendarElement> for Venue {
-    fn into(self) -> CalendarElement {
-        CalendarElement::Venue(self)
+impl From<Venue> for CalendarElement {
+    fn from(val: Venue) -> Self {
+        CalendarElement::Venue(val)
+    }
+}
+
+impl From<Timezone> for CalendarElement {
+    fn from(val: Timezone) -> Self {
+        CalendarElement::Timezone(val)
     }
 }
 
 impl CalendarElement {
-    fn fmt_write<W: fmt::Write>(&self, out: &mut W) -> Result<(), fmt::Error> {
+    fn fmt_write<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        options: &WriteOptions,
+    ) -> Result<(), fmt::Error> {
+        match *self {
+            CalendarElement::Todo(ref todo) => todo.fmt_write_with(out, options),
+            CalendarElement::Event(ref event) => event.fmt_write_with(out, options),
+            CalendarElement::Journal(ref journal) => journal.fmt_write_with(out, options),
+            CalendarElement::FreeBusy(ref free_busy) => free_busy.fmt_write_with(out, options),
+            CalendarElement::Venue(ref venue) => venue.fmt_write_with(out, options),
+            CalendarElement::Timezone(ref timezone) => timezone.fmt_write(out),
+        }
+    }
+
+    /// The UID of the component, time zones have none
+    fn uid(&self) -> Option<&str> {
+        match *self {
+            CalendarElement::Todo(ref todo) => todo.get_uid(),
+            CalendarElement::Event(ref event) => event.get_uid(),
+            CalendarElement::Journal(ref journal) => journal.get_uid(),
+            CalendarElement::FreeBusy(ref free_busy) => free_busy.get_uid(),
+            CalendarElement::Venue(ref venue) => venue.get_uid(),
+            CalendarElement::Timezone(_) => None,
+        }
+    }
+
+    #[cfg(any(feature = "jcal", feature = "xcal"))]
+    fn to_raw(&self, options: &WriteOptions) -> RawComponent {
         match *self {
-            CalendarElement::Todo(ref todo) => todo.fmt_write(out),
-            CalendarElement::Event(ref event) => event.fmt_write(out),
-            CalendarElement::Venue(ref venue) => venue.fmt_write(out),
+            CalendarElement::Todo(ref todo) => to_raw(todo, options),
+            CalendarElement::Event(ref event) => to_raw(event, options),
+            CalendarElement::Journal(ref journal) => to_raw(journal, options),
+            CalendarElement::FreeBusy(ref free_busy) => to_raw(free_busy, options),
+            CalendarElement::Venue(ref venue) => to_raw(venue, options),
+            CalendarElement::Timezone(ref timezone) => timezone.to_raw(),
         }
     }
 }
@@ -45,10 +132,11 @@ impl CalendarElement {
 /// Represents a calendar
 ///
 /// You can `.add()` `Component`s to this.
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Calendar {
-    properties: Vec<Property>,
-    components: Vec<CalendarElement>,
+    pub(crate) properties: Vec<Property>,
+    pub(crate) components: Vec<CalendarElement>,
 }
 
 impl Calendar {
@@ -69,11 +157,107 @@ impl Calendar {
     }
 
     /// Append a given `Property` to the `Calendar`
+    ///
+    /// VERSION, PRODID, CALSCALE and METHOD replace an earlier property with the same key.
     pub fn append_property(&mut self, property: Property) -> &mut Self {
+        if SINGLE_PROPERTIES.contains(&property.key()) {
+            self.properties
+                .retain(|other| other.key() != property.key());
+        }
         self.properties.push(property);
         self
     }
 
+    /// The `Property`s of the calendar itself, such as NAME or X-WR-CALNAME
+    pub fn properties(&self) -> &[Property] {
+        &self.properties
+    }
+
+    /// Mutable access to each `Property` of the calendar itself
+    ///
+    /// Properties are added with [`append_property`](Calendar::append_property), which keeps
+    /// VERSION, PRODID, CALSCALE and METHOD single.
+    pub fn properties_mut(&mut self) -> impl Iterator<Item = &mut Property> {
+        self.properties.iter_mut()
+    }
+
+    /// Keeps only the calendar `Property`s for which `predicate` returns `true`
+    pub fn retain_properties<F: FnMut(&Property) -> bool>(&mut self, predicate: F) -> &mut Self {
+        self.properties.retain(predicate);
+        self
+    }
+
+    /// Removes the first calendar `Property` with the given key and returns it
+    ///
+    /// See [`remove_properties`](Calendar::remove_properties) to remove all of them.
+    pub fn remove_property(&mut self, key: &str) -> Option<Property> {
+        let index = self
+            .properties
+            .iter()
+            .position(|property| property.key() == key)?;
+        Some(self.properties.remove(index))
+    }
+
+    /// Removes all calendar `Property`s with the given key and returns them
+    pub fn remove_properties(&mut self, key: &str) -> Vec<Property> {
+        let (removed, kept) = self
+            .properties
+            .drain(..)
+            .partition(|property| property.key() == key);
+        self.properties = kept;
+        removed
+    }
+
+    /// All `Event`s of the calendar
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.components
+            .iter()
+            .filter_map(|component| match component {
+                CalendarElement::Event(event) => Some(event),
+                _ => None,
+            })
+    }
+
+    /// All `Todo`s of the calendar
+    pub fn todos(&self) -> impl Iterator<Item = &Todo> {
+        self.components
+            .iter()
+            .filter_map(|component| match component {
+                CalendarElement::Todo(todo) => Some(todo),
+                _ => None,
+            })
+    }
+
+    /// All `Venue`s of the calendar
+    pub fn venues(&self) -> impl Iterator<Item = &Venue> {
+        self.components
+            .iter()
+            .filter_map(|component| match component {
+                CalendarElement::Venue(venue) => Some(venue),
+                _ => None,
+            })
+    }
+
+    /// The first component with the given UID
+    ///
+    /// Overrides of single occurrences share the UID of their recurring event, this finds
+    /// whichever comes first.
+    pub fn find_by_uid(&self, uid: &str) -> Option<&CalendarElement> {
+        self.components
+            .iter()
+            .find(|component| component.uid() == Some(uid))
+    }
+
+    /// Removes all components with the given UID and returns them
+    pub fn remove_by_uid(&mut self, uid: &str) -> Vec<CalendarElement> {
+        let (removed, kept) = self
+            .components
+            .drain(..)
+            .partition(|component| component.uid() == Some(uid));
+        self.components = kept;
+        removed
+    }
+
     /// Extends this `Calendar` with the contends of another.
     pub fn extend<T, U>(&mut self, other: T)
     where
@@ -89,6 +273,30 @@ impl Calendar {
         self
     }
 
+    /// Set the PRODID `Property`, which defaults to `ICALENDAR-RS`
+    ///
+    /// RFC 5545 suggests the form `-//Company//Product//EN`.
+    ///
+    /// ```rust
+    /// # use icalendar::*;
+    /// let mut calendar = Calendar::new();
+    /// calendar.product_id("-//ACME//Planner 1.0//EN");
+    /// assert!(calendar.to_string().contains("PRODID:-//ACME//Planner 1.0//EN\r\n"));
+    /// ```
+    pub fn product_id(&mut self, product_id: &str) -> &mut Self {
+        self.append_property(Property::new("PRODID", product_id))
+    }
+
+    /// Set the VERSION `Property`, which defaults to `2.0`
+    pub fn version(&mut self, version: &str) -> &mut Self {
+        self.append_property(Property::new("VERSION", version))
+    }
+
+    /// Set the CALSCALE `Property`, which defaults to `GREGORIAN`
+    pub fn scale(&mut self, scale: &str) -> &mut Self {
+        self.append_property(Property::new("CALSCALE", scale))
+    }
+
     /// Set the NAME and X-WR-CALNAME `Property`s
     pub fn name(&mut self, name: &str) -> &mut Self {
         self.append_property(Property::new("NAME", name));
@@ -122,24 +330,285 @@ impl Calendar {
         self
     }
 
+    /// Adds a [`Timezone`] for every `TZID` that the components refer to.
+    ///
+    /// The definitions are derived from the IANA time zone database with
+    /// [`Timezone::from_tz`] and cover all date-times of a zone, including the last occurrence of
+    /// recurrences limited by COUNT or UNTIL. Time zones that are already defined or that are not
+    /// known to `chrono-tz` are skipped.
+    ///
+    /// Only available with the `chrono-tz` feature.
+    #[cfg(feature = "chrono-tz")]
+    pub fn add_timezones(&mut self) -> &mut Self {
+        let mut ranges = BTreeMap::new();
+        let mut defined = Vec::new();
+        for component in &self.components {
+            match component {
+                CalendarElement::Todo(todo) => collect_timezone_ranges(todo, &mut ranges),
+                CalendarElement::Event(event) => collect_timezone_ranges(event, &mut ranges),
+                CalendarElement::Journal(journal) => collect_timezone_ranges(journal, &mut ranges),
+                CalendarElement::FreeBusy(free_busy) => {
+                    collect_timezone_ranges(free_busy, &mut ranges)
+                }
+                CalendarElement::Venue(venue) => collect_timezone_ranges(venue, &mut ranges),
+                CalendarElement::Timezone(timezone) => defined.extend(timezone.tzid()),
+            }
+        }
+        let margin = Duration::days(1);
+        let timezones = ranges
+            .into_iter()
+            .filter(|(tzid, _)| !defined.contains(&tzid.as_str()))
+            .filter_map(|(tzid, (start, end))| {
+                let tz = tzid.parse::<chrono_tz::Tz>().ok()?;
+                let start = DateTime::<Utc>::from_naive_utc_and_offset(start - margin, Utc);
+                let end = DateTime::<Utc>::from_naive_utc_and_offset(end + margin, Utc);
+                Some(Timezone::from_tz(tz, start, end))
+            })
+            .collect::<Vec<_>>();
+        self.extend(timezones);
+        self
+    }
+
+    /// Computes when the events of this `Calendar` keep their attendees busy between `start` and
+    /// `end`.
+    ///
+    /// The resulting [`FreeBusy`] only has DTSTART, DTEND and FREEBUSY properties, so it can be
+    /// published without revealing any details of the events. Recurring events are expanded and
+    /// overlapping periods are merged. Events with `TRANSP:TRANSPARENT` or `STATUS:CANCELLED` are
+    /// left out, those with `STATUS:TENTATIVE` are reported as
+    /// [`BusyTentative`](FreeBusyType::BusyTentative).
+    ///
+    /// Time zone-qualified date-times are resolved with the [`Timezone`]s of this calendar, see
+    /// also `add_timezones`. With the `chrono-tz` feature a `TZID` that no `Timezone` defines is
+    /// looked up in the IANA time zone database, events whose time zone can not be resolved are
+    /// left out. Floating date-times and all day events are taken as UTC.
+    pub fn free_busy(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> FreeBusy {
+        let window = Period::new(start, end);
+        let timezones = self
+            .components
+            .iter()
+            .filter_map(|component| match component {
+                CalendarElement::Timezone(timezone) => Some((timezone.tzid()?, timezone)),
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+        let to_utc = |date_time: &CalendarDateTime| match date_time {
+            CalendarDateTime::Utc(utc) => Some(*utc),
+            CalendarDateTime::Floating(naive) => Some(Utc.from_utc_datetime(naive)),
+            CalendarDateTime::WithTimezone { date_time, tzid } => {
+                let offset = match timezones.get(tzid.as_str()) {
+                    Some(timezone) => timezone.utc_offset(*date_time)?,
+                    None => known_utc_offset(tzid, date_time)?,
+                };
+                let offset = Duration::seconds(i64::from(offset.local_minus_utc()));
+                Some(Utc.from_utc_datetime(&(*date_time - offset)))
+            }
+        };
+
+        let mut busy = HashMap::<FreeBusyType, Vec<Period>>::new();
+        for event in self.events() {
+            let properties = event.properties();
+            let value = |key| properties.get(key).map(Property::value);
+            if value("TRANSP").as_deref() == Some("TRANSPARENT") {
+                continue;
+            }
+            let fb_type = match value("STATUS").as_deref() {
+                Some("CANCELLED") => continue,
+                Some("TENTATIVE") => FreeBusyType::BusyTentative,
+                _ => FreeBusyType::Busy,
+            };
+            let event_start = match properties
+                .get("DTSTART")
+                .and_then(|start| date_times(start).into_iter().next())
+                .and_then(|start| to_utc(&start))
+            {
+                Some(event_start) => event_start,
+                None => continue,
+            };
+            // DTEND gives every occurrence the same exact duration, DURATION is added to the
+            // wall clock time of each
+            let exact = properties.contains_key("DTEND");
+            let duration = if let Some(ends) = properties.get("DTEND").map(date_times) {
+                ends.first().and_then(&to_utc).map(|end| end - event_start)
+            } else if let Some(duration) = value("DURATION") {
+                parse_duration(&duration)
+            } else {
+                // an all day event without end lasts the day
+                value("DTSTART")
+                    .filter(|start| start.len() == 8)
+                    .map(|_| Duration::days(1))
+            };
+            let duration = match duration {
+                Some(duration) if duration > Duration::zero() => duration,
+                _ => continue,
+            };
+
+            // the occurrences are found by wall clock time, so leave room for any offset
+            let margin = Duration::days(1);
+            let occurrences = event.occurrences(
+                start.naive_utc() - duration - margin,
+                end.naive_utc() + margin,
+            );
+            for occurrence in occurrences {
+                let occurrence_start = match to_utc(&occurrence) {
+                    Some(occurrence_start) => occurrence_start,
+                    None => continue,
+                };
+                let occurrence_end = if exact {
+                    Some(occurrence_start + duration)
+                } else {
+                    to_utc(&occurrence.with_naive(occurrence.naive() + duration))
+                };
+                let period = match occurrence_end {
+                    Some(occurrence_end) => Period::new(occurrence_start, occurrence_end),
+                    None => continue,
+                };
+                if let Some(period) = period.intersection(&window) {
+                    busy.entry(fb_type).or_default().push(period);
+                }
+            }
+        }
+
+        let mut free_busy = FreeBusy::new();
+        free_busy.starts(window.start()).ends(window.end());
+        for fb_type in &[FreeBusyType::Busy, FreeBusyType::BusyTentative] {
+            if let Some(periods) = busy.remove(fb_type) {
+                free_busy.free_busy(*fb_type, merge_periods(periods));
+            }
+        }
+        free_busy.done()
+    }
+
+    /// The properties as written, the header properties first and filled in with their defaults
+    ///
+    /// Of the properties that must not occur more than once only the last one is written, even
+    /// if more of them got in, for example by deserializing.
+    fn written_properties(&self) -> impl Iterator<Item = Cow<'_, Property>> {
+        let headers = HEADER_PROPERTIES.iter().map(move |(key, default)| {
+            self.properties
+                .iter()
+                .rev()
+                .find(|property| property.key() == *key)
+                .map_or_else(|| Cow::Owned(Property::new(key, default)), Cow::Borrowed)
+        });
+        let others = self
+            .properties
+            .iter()
+            .enumerate()
+            .filter(move |(index, property)| {
+                let key = property.key();
+                let header = HEADER_PROPERTIES.iter().any(|(header, _)| key == *header);
+                let replaced = SINGLE_PROPERTIES.contains(&key)
+                    && self.properties[index + 1..]
+                        .iter()
+                        .any(|later| later.key() == key);
+                !header && !replaced
+            })
+            .map(|(_, property)| Cow::Borrowed(property));
+        headers.chain(others)
+    }
+
     /// Writes `Calendar` into a `Writer` using `std::fmt`.
-    fn fmt_write<W: fmt::Write>(&self, out: &mut W) -> Result<(), fmt::Error> {
+    fn fmt_write<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        options: &WriteOptions,
+    ) -> Result<(), fmt::Error> {
         write_crlf!(out, "BEGIN:VCALENDAR")?;
-        write_crlf!(out, "VERSION:2.0")?;
-        write_crlf!(out, "PRODID:ICALENDAR-RS")?;
-        write_crlf!(out, "CALSCALE:GREGORIAN")?;
-
-        for property in &self.properties {
+        for property in self.written_properties() {
             property.fmt_write(out)?;
         }
 
-        for component in &self.components {
-            component.fmt_write(out)?;
+        // time zones are defined before the components referencing them
+        let (timezones, components): (Vec<_>, Vec<_>) = self
+            .components
+            .iter()
+            .partition(|component| matches!(component, CalendarElement::Timezone(_)));
+        for component in timezones.into_iter().chain(components) {
+            component.fmt_write(out, options)?;
         }
         write_crlf!(out, "END:VCALENDAR")?;
         Ok(())
     }
 
+    /// The calendar as a tree of `RawComponent`s, holding the same as written by `fmt_write`
+    #[cfg(any(feature = "jcal", feature = "xcal"))]
+    pub(crate) fn to_raw(&self, options: &WriteOptions) -> RawComponent {
+        let mut raw = RawComponent::new(String::from("VCALENDAR"));
+        raw.properties
+            .extend(self.written_properties().map(Cow::into_owned));
+
+        let (timezones, components): (Vec<_>, Vec<_>) = self
+            .components
+            .iter()
+            .partition(|component| matches!(component, CalendarElement::Timezone(_)));
+        raw.components.extend(
+            timezones
+                .into_iter()
+                .chain(components)
+                .map(|component| component.to_raw(options)),
+        );
+        raw
+    }
+
+    /// Stores a DTSTAMP and UID in every component lacking them, made up as `options` say
+    ///
+    /// Later exports of the calendar then keep the identity of its components.
+    pub fn ensure_uids(&mut self, options: &WriteOptions) -> &mut Self {
+        for component in &mut self.components {
+            match component {
+                CalendarElement::Todo(todo) => fill_in(todo, options),
+                CalendarElement::Event(event) => fill_in(event, options),
+                CalendarElement::Journal(journal) => fill_in(journal, options),
+                CalendarElement::FreeBusy(free_busy) => fill_in(free_busy, options),
+                CalendarElement::Venue(venue) => fill_in(venue, options),
+                CalendarElement::Timezone(_) => {}
+            }
+        }
+        self
+    }
+
+    /// Writes the calendar with a missing DTSTAMP and UID of its components filled in as
+    /// `options` say, see [`WriteOptions`]
+    pub fn to_string_with(&self, options: &WriteOptions) -> String {
+        let mut out = String::new();
+        self.fmt_write(&mut out, options).unwrap();
+        out
+    }
+
+    /// Streams the calendar into `out`, folding lines on the way instead of building a `String`
+    ///
+    /// Many small writes are made, so files and sockets are best wrapped in a
+    /// [`BufWriter`](io::BufWriter).
+    ///
+    /// ```rust
+    /// # use icalendar::*;
+    /// let mut calendar = Calendar::new();
+    /// calendar.push(Event::new().summary("Planning").done());
+    ///
+    /// let mut out = Vec::new();
+    /// calendar.write_to(&mut out).unwrap();
+    /// assert!(String::from_utf8(out).unwrap().contains("SUMMARY:Planning\r\n"));
+    /// ```
+    pub fn write_to<W: io::Write>(&self, out: W) -> io::Result<()> {
+        self.write_to_with(out, &WriteOptions::default())
+    }
+
+    /// Like [`write_to`](Calendar::write_to), with a missing DTSTAMP and UID of the components
+    /// filled in as `options` say
+    pub fn write_to_with<W: io::Write>(&self, out: W, options: &WriteOptions) -> io::Result<()> {
+        let mut writer = IoWriter {
+            inner: out,
+            error: None,
+        };
+        match self.fmt_write(&mut writer, options) {
+            Ok(()) => writer.inner.flush(),
+            Err(fmt::Error) => Err(writer
+                .error
+                .unwrap_or_else(|| io::Error::other("formatting the calendar failed"))),
+        }
+    }
+
     /// Prints to stdout
     pub fn print(&self) -> Result<(), fmt::Error> {
         print_crlf!("{}", self);
@@ -147,9 +616,95 @@ impl Calendar {
     }
 }
 
+/// Lets the `fmt::Write` based writing go into an `io::Write`, keeping the I/O error.
+struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Stores the DTSTAMP and UID that writing `component` would fill in.
+fn fill_in<C: Component>(component: &mut C, options: &WriteOptions) {
+    if !component.properties().contains_key("UID") {
+        let uid = options.uid(component);
+        component.uid(&uid);
+    }
+    if !component.properties().contains_key("DTSTAMP") {
+        let now = CalendarDateTime::Utc(options.timestamp());
+        component.append_property(now.to_property("DTSTAMP"));
+    }
+}
+
+/// How many years past its last fixed date-time a limited recurrence is followed when adding
+/// time zones
+#[cfg(feature = "chrono-tz")]
+const RECURRENCE_HORIZON_YEARS: i64 = 5;
+
+/// Widens the range of wall clock times used per `TZID` by the date-times of `component`.
+#[cfg(feature = "chrono-tz")]
+fn collect_timezone_ranges<C: Component>(
+    component: &C,
+    ranges: &mut BTreeMap<String, (NaiveDateTime, NaiveDateTime)>,
+) {
+    let mut extend = |tzid: &str, date_time: NaiveDateTime| {
+        let range = ranges
+            .entry(tzid.to_owned())
+            .or_insert((date_time, date_time));
+        range.0 = range.0.min(date_time);
+        range.1 = range.1.max(date_time);
+    };
+
+    let properties = component.properties();
+    let mut last_fixed = None;
+    for property in properties.values() {
+        for date_time in date_times(property) {
+            if let CalendarDateTime::WithTimezone { date_time, tzid } = date_time {
+                last_fixed = last_fixed.max(Some(date_time));
+                extend(&tzid, date_time);
+            }
+        }
+    }
+
+    // unlimited recurrences are covered by the open ended rules of the time zone, limited ones
+    // are followed no further than a few years past the fixed date-times
+    let rule = properties
+        .get("RRULE")
+        .and_then(|rrule| rrule.value().parse::<RecurrenceRule>().ok())
+        .filter(|rule| rule.limit.is_some());
+    let start = properties
+        .get("DTSTART")
+        .and_then(|start| date_times(start).into_iter().next());
+    if let (
+        Some(rule),
+        Some(CalendarDateTime::WithTimezone { date_time, tzid }),
+        Some(last_fixed),
+    ) = (rule, start, last_fixed)
+    {
+        let horizon = last_fixed + Duration::days(RECURRENCE_HORIZON_YEARS * 366);
+        let last = match rule.limit {
+            Some(Limit::Until(until)) => Some(until.naive().min(horizon)),
+            _ => component
+                .occurrences(date_time, horizon)
+                .last()
+                .map(|last| last.naive()),
+        };
+        if let Some(last) = last {
+            extend(&tzid, last);
+        }
+    }
+}
+
 impl fmt::Display for Calendar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.fmt_write(f)
+        self.fmt_write(f, &WriteOptions::default())
     }
 }
 
@@ -161,6 +716,12 @@ impl Deref for Calendar {
     }
 }
 
+impl DerefMut for Calendar {
+    fn deref_mut(&mut self) -> &mut [CalendarElement] {
+        self.components.deref_mut()
+    }
+}
+
 impl<C: Into<CalendarElement>> FromIterator<C> for Calendar {
     fn from_iter<T: IntoIterator<Item = C>>(iter: T) -> Self {
         Calendar {
@@ -170,6 +731,26 @@ impl<C: Into<CalendarElement>> FromIterator<C> for Calendar {
     }
 }
 
+/// The offset from UTC that the IANA time zone database knows for the wall clock time `local`
+/// in the zone `tzid`
+///
+/// Wall clock times that are skipped when the clocks are set forward take the offset from before.
+#[cfg(feature = "chrono-tz")]
+fn known_utc_offset(tzid: &str, local: &NaiveDateTime) -> Option<FixedOffset> {
+    let tz = tzid.parse::<chrono_tz::Tz>().ok()?;
+    let offset = tz
+        .offset_from_local_datetime(local)
+        .earliest()
+        .unwrap_or_else(|| tz.offset_from_utc_datetime(local));
+    Some(offset.fix())
+}
+
+/// Without the `chrono-tz` feature only [`Timezone`]s tell offsets
+#[cfg(not(feature = "chrono-tz"))]
+fn known_utc_offset(_tzid: &str, _local: &NaiveDateTime) -> Option<FixedOffset> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +773,102 @@ mod tests {
         calendar.extend(events);
         assert_eq!(calendar.components.len(), 2);
     }
+
+    #[test]
+    fn calendar_header_properties() {
+        let mut calendar = Calendar::new();
+        calendar
+            .product_id("-//ACME//Planner 1.0//EN")
+            .append_property(Property::new("PRODID", "-//ACME//Planner 2.0//EN"))
+            .append_property(Property::new("METHOD", "PUBLISH"))
+            .name("Team");
+        let ics = calendar.to_string();
+        assert!(ics.starts_with(
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//ACME//Planner 2.0//EN\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             METHOD:PUBLISH\r\n"
+        ));
+        assert_eq!(ics.matches("PRODID:").count(), 1);
+
+        let parsed: Calendar = ics.parse().unwrap();
+        assert_eq!(parsed.to_string(), ics);
+    }
+
+    #[test]
+    fn calendar_single_properties_are_written_once() {
+        let mut calendar = Calendar::new();
+        // as if deserialized, without going through `append_property`
+        calendar.properties.extend(vec![
+            Property::new("METHOD", "PUBLISH"),
+            Property::new("VERSION", "2.0"),
+            Property::new("NAME", "Team"),
+            Property::new("METHOD", "REQUEST"),
+            Property::new("VERSION", "2.1"),
+        ]);
+        assert_eq!(
+            calendar.to_string(),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.1\r\n\
+             PRODID:ICALENDAR-RS\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             NAME:Team\r\n\
+             METHOD:REQUEST\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+
+    /// Accepts `limit` bytes, then fails
+    struct Full {
+        limit: usize,
+    }
+
+    impl io::Write for Full {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf.len() > self.limit {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            self.limit -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn calendar_write_to() {
+        let mut calendar = Calendar::new();
+        calendar.push(
+            Event::new()
+                .description(&"long; and escaped, ".repeat(20))
+                .done(),
+        );
+        calendar.ensure_uids(&WriteOptions::new());
+        let mut out = Vec::new();
+        calendar.write_to(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), calendar.to_string());
+
+        let error = calendar.write_to(Full { limit: 100 }).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn calendar_ensure_uids() {
+        let mut calendar = Calendar::new();
+        calendar.push(Event::new().uid("kept").done());
+        calendar.push(Todo::new());
+        calendar.push(Timezone::new("Europe/Berlin"));
+        calendar.ensure_uids(&WriteOptions::new());
+        assert_eq!(calendar.to_string(), calendar.to_string());
+        match &calendar.components[0] {
+            CalendarElement::Event(event) => {
+                assert_eq!(event.properties()["UID"].value(), "kept");
+                assert!(event.properties().contains_key("DTSTAMP"));
+            }
+            other => panic!("expected an event, got {:?}", other),
+        }
+    }
 }
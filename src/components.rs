@@ -1,23 +1,28 @@
 use chrono::*;
-use uuid::Uuid;
 
 // use std::io;
-use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::mem;
 
+use crate::alarm::Alarm;
+use crate::attendee::{Attendee, Organizer};
+use crate::occurrences::Occurrences;
+use crate::output::WriteOptions;
+#[cfg(any(feature = "jcal", feature = "xcal"))]
+use crate::parser::RawComponent;
+use crate::period::Period;
 use crate::properties::*;
+use crate::property_map::{MultiPropertiesMut, PropertyMap};
+use crate::repeats::RecurrenceRule;
+use crate::value::Value;
 
 /// Representation of various forms of `DATE-TIME` per
 /// [RFC 5545, Section 3.3.5](https://tools.ietf.org/html/rfc5545#section-3.3.5)
 ///
 /// Conversions from [chrono] types are provided in form of [From] implementations, see
 /// documentation of individual variants.
-///
-/// In addition to readily implemented `FORM #1` and `FORM #2`, the RFC also specifies
-/// `FORM #3: DATE WITH LOCAL TIME AND TIME ZONE REFERENCE`. This variant is not yet implemented.
-/// Adding it will require adding support for `VTIMEZONE` and referencing it using `TZID`.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum CalendarDateTime {
     /// `FORM #1: DATE WITH LOCAL TIME`: floating, follows current time-zone of the attendee.
     ///
@@ -28,14 +33,84 @@ pub enum CalendarDateTime {
     /// Conversion from [`chrono::DateTime<Utc>`](DateTime) results in this variant. Use
     /// `date_time.with_timezone(&Utc)` to convert `date_time` from arbitrary time zone to UTC.
     Utc(DateTime<Utc>),
+    /// `FORM #3: DATE WITH LOCAL TIME AND TIME ZONE REFERENCE`: rendered with a `TZID` parameter.
+    ///
+    /// The `tzid` should match the `TZID` of a [`Timezone`](crate::Timezone) in the same
+    /// calendar. Conversion from a `(NaiveDateTime, &str)` tuple results in this variant.
+    WithTimezone {
+        /// Local time in the referenced time zone
+        date_time: NaiveDateTime,
+        /// Identifier of the time zone, e.g. `Europe/Berlin`
+        tzid: String,
+    },
 }
 
 impl fmt::Display for CalendarDateTime {
     /// Format date-time in RFC 5545 compliant manner.
+    ///
+    /// The `TZID` of [CalendarDateTime::WithTimezone] is not part of the value, it is added as
+    /// parameter by the setters of [Component].
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
             CalendarDateTime::Floating(naive_dt) => naive_dt.format("%Y%m%dT%H%M%S").fmt(f),
             CalendarDateTime::Utc(utc_dt) => utc_dt.format("%Y%m%dT%H%M%SZ").fmt(f),
+            CalendarDateTime::WithTimezone { date_time, .. } => {
+                date_time.format("%Y%m%dT%H%M%S").fmt(f)
+            }
+        }
+    }
+}
+
+impl CalendarDateTime {
+    /// Wall clock time for floating and time zone-qualified date-times, UTC time otherwise.
+    pub(crate) fn naive(&self) -> NaiveDateTime {
+        match self {
+            CalendarDateTime::Floating(naive_dt) => *naive_dt,
+            CalendarDateTime::Utc(utc_dt) => utc_dt.naive_utc(),
+            CalendarDateTime::WithTimezone { date_time, .. } => *date_time,
+        }
+    }
+
+    /// Creates a date-time of the same form from a value returned by [`naive`](Self::naive).
+    pub(crate) fn with_naive(&self, naive: NaiveDateTime) -> Self {
+        match self {
+            CalendarDateTime::Floating(_) => CalendarDateTime::Floating(naive),
+            CalendarDateTime::Utc(_) => CalendarDateTime::Utc(Utc.from_utc_datetime(&naive)),
+            CalendarDateTime::WithTimezone { tzid, .. } => CalendarDateTime::WithTimezone {
+                date_time: naive,
+                tzid: tzid.clone(),
+            },
+        }
+    }
+
+    /// Creates a `Property` with this date-time as value and, if needed, a `TZID` parameter.
+    pub(crate) fn to_property(&self, key: &str) -> Property {
+        Property::with_value(key, self.clone())
+    }
+
+    /// Reads a date-time from the value of `property`, taking its `TZID` parameter into account.
+    pub(crate) fn from_property_value(property: &Property, value: &str) -> Option<Self> {
+        match (Self::parse(value)?, property.parameter("TZID")) {
+            (CalendarDateTime::Floating(date_time), Some(tzid)) => {
+                Some(CalendarDateTime::WithTimezone {
+                    date_time,
+                    tzid: tzid.value().to_owned(),
+                })
+            }
+            (date_time, _) => Some(date_time),
+        }
+    }
+
+    /// Parses the textual `DATE-TIME` form written by the `Display` implementation.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        if let Some(utc) = s.strip_suffix('Z') {
+            NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(|naive| CalendarDateTime::Utc(Utc.from_utc_datetime(&naive)))
+        } else {
+            NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(CalendarDateTime::Floating)
         }
     }
 }
@@ -54,28 +129,116 @@ impl From<NaiveDateTime> for CalendarDateTime {
     }
 }
 
+/// Converts from local date-time and time zone identifier to [CalendarDateTime::WithTimezone].
+impl From<(NaiveDateTime, &str)> for CalendarDateTime {
+    fn from((date_time, tzid): (NaiveDateTime, &str)) -> Self {
+        Self::WithTimezone {
+            date_time,
+            tzid: tzid.to_owned(),
+        }
+    }
+}
+
+/// Either a `DATE-TIME` or, for all day components, a `DATE`
+///
+/// Returned by [`Component::get_start`], [`Component::get_end`] and [`Todo::get_due`], and
+/// accepted by the matching setters. Besides the conversions into [`CalendarDateTime`], a
+/// [`chrono::NaiveDate`] converts into [`DatePerhapsTime::Date`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum DatePerhapsTime {
+    /// A point in time
+    DateTime(CalendarDateTime),
+    /// A whole day, written with `VALUE=DATE`
+    Date(NaiveDate),
+}
+
+impl DatePerhapsTime {
+    /// Creates a `Property` with this date or date-time as value.
+    pub(crate) fn to_property(&self, key: &str) -> Property {
+        match self {
+            DatePerhapsTime::DateTime(date_time) => date_time.to_property(key),
+            DatePerhapsTime::Date(date) => Property::with_value(key, *date),
+        }
+    }
+
+    /// Reads a DATE or DATE-TIME value, anything else is `None`
+    fn from_property(property: &Property) -> Option<Self> {
+        match property.typed_value() {
+            Value::DateTime(date_time) => Some(DatePerhapsTime::DateTime(date_time)),
+            Value::Date(date) => Some(DatePerhapsTime::Date(date)),
+            _ => None,
+        }
+    }
+}
+
+impl From<CalendarDateTime> for DatePerhapsTime {
+    fn from(date_time: CalendarDateTime) -> Self {
+        DatePerhapsTime::DateTime(date_time)
+    }
+}
+
+impl From<DateTime<Utc>> for DatePerhapsTime {
+    fn from(date_time: DateTime<Utc>) -> Self {
+        DatePerhapsTime::DateTime(date_time.into())
+    }
+}
+
+impl From<NaiveDateTime> for DatePerhapsTime {
+    fn from(date_time: NaiveDateTime) -> Self {
+        DatePerhapsTime::DateTime(date_time.into())
+    }
+}
+
+impl From<(NaiveDateTime, &str)> for DatePerhapsTime {
+    fn from(date_time: (NaiveDateTime, &str)) -> Self {
+        DatePerhapsTime::DateTime(date_time.into())
+    }
+}
+
+impl From<NaiveDate> for DatePerhapsTime {
+    fn from(date: NaiveDate) -> Self {
+        DatePerhapsTime::Date(date)
+    }
+}
+
 /// VEVENT [(RFC 5545, Section 3.6.1 )](https://tools.ietf.org/html/rfc5545#section-3.6.1)
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Event {
     inner: InnerComponent,
+    alarms: Vec<Alarm>,
 }
 
 /// VTODO  [(RFC 5545, Section 3.6.2 )](https://tools.ietf.org/html/rfc5545#section-3.6.2)
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Todo {
     inner: InnerComponent,
+    alarms: Vec<Alarm>,
+}
+
+/// VJOURNAL  [(RFC 5545, Section 3.6.3 )](https://tools.ietf.org/html/rfc5545#section-3.6.3)
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+    inner: InnerComponent,
+}
+
+/// VFREEBUSY  [(RFC 5545, Section 3.6.4 )](https://tools.ietf.org/html/rfc5545#section-3.6.4)
+///
+/// Publishes when someone is busy, without telling why. Usually computed with
+/// [`Calendar::free_busy`](crate::Calendar::free_busy).
+#[derive(Clone, Debug, Default)]
+pub struct FreeBusy {
+    inner: InnerComponent,
 }
 
 /// VVENUE  [(ical-venue)](https://tools.ietf.org/html/draft-norris-ical-venue-01)
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Venue {
     inner: InnerComponent,
 }
 
-#[derive(Debug, Default)]
-struct InnerComponent {
-    properties: BTreeMap<String, Property>,
-    multi_properties: Vec<Property>,
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InnerComponent {
+    pub(crate) properties: PropertyMap,
 }
 
 impl InnerComponent {
@@ -83,10 +246,50 @@ impl InnerComponent {
     /// copies over everything
     pub fn done(&mut self) -> Self {
         InnerComponent {
-            properties: mem::replace(&mut self.properties, BTreeMap::new()),
-            multi_properties: mem::replace(&mut self.multi_properties, Vec::new()),
+            properties: mem::take(&mut self.properties),
         }
     }
+
+    /// Writes all properties in order of their keys.
+    pub(crate) fn fmt_write_properties<W: fmt::Write>(
+        &self,
+        out: &mut W,
+    ) -> Result<(), fmt::Error> {
+        for property in self.properties.values() {
+            property.fmt_write(out)?;
+        }
+        Ok(())
+    }
+
+    /// All properties as a `RawComponent` of the given kind, in the order they are written
+    #[cfg(any(feature = "jcal", feature = "xcal"))]
+    pub(crate) fn to_raw(&self, kind: &str) -> RawComponent {
+        let mut raw = RawComponent::new(kind.to_owned());
+        raw.properties.extend(self.properties.values().cloned());
+        raw
+    }
+}
+
+/// `component` as a `RawComponent`, with a missing DTSTAMP and UID filled in as `options` say
+///
+/// Properties come in the same order as written by [`Component::fmt_write_with`].
+#[cfg(any(feature = "jcal", feature = "xcal"))]
+pub(crate) fn to_raw<C: Component + ?Sized>(component: &C, options: &WriteOptions) -> RawComponent {
+    let mut raw = RawComponent::new(C::component_kind().to_owned());
+    if !component.properties().contains_key("DTSTAMP") {
+        let now = CalendarDateTime::Utc(options.timestamp());
+        raw.properties
+            .push(Property::new("DTSTAMP", &now.to_string()));
+    }
+    raw.properties
+        .extend(component.properties().values().cloned());
+    if !component.properties().contains_key("UID") {
+        raw.properties
+            .push(Property::new("UID", &options.uid(component)));
+    }
+    raw.components
+        .extend(component.alarms().iter().map(Alarm::to_raw));
+    raw
 }
 
 impl Event {
@@ -100,6 +303,7 @@ impl Event {
     pub fn done(&mut self) -> Self {
         Event {
             inner: self.inner.done(),
+            alarms: mem::take(&mut self.alarms),
         }
     }
 
@@ -109,9 +313,16 @@ impl Event {
         self
     }
 
-    //pub fn repeats<R:Repeater+?Sized>(&mut self, repeat: R) -> &mut Self {
-    //    unimplemented!()
-    //}
+    /// Gets the overall status
+    pub fn get_status(&self) -> Option<EventStatus> {
+        self.properties().get("STATUS")?.value().parse().ok()
+    }
+
+    /// Adds a VALARM, relative triggers refer to DTSTART or DTEND
+    pub fn alarm(&mut self, alarm: Alarm) -> &mut Self {
+        self.alarms.push(alarm);
+        self
+    }
 }
 
 impl Todo {
@@ -125,6 +336,7 @@ impl Todo {
     pub fn done(&mut self) -> Self {
         Todo {
             inner: self.inner.done(),
+            alarms: mem::take(&mut self.alarms),
         }
     }
 
@@ -132,16 +344,15 @@ impl Todo {
     ///
     /// Ranges between 0 - 100
     pub fn percent_complete(&mut self, percent: u8) -> &mut Self {
-        self.add_property("PERCENT-COMPLETE", &percent.to_string());
+        self.append_property(Property::with_value("PERCENT-COMPLETE", i32::from(percent)));
         self
     }
 
     /// Set the DUE `Property`
     ///
-    /// See [CalendarDateTime] for info how are different [chrono] types converted automatically.
-    pub fn due<T: Into<CalendarDateTime>>(&mut self, dt: T) -> &mut Self {
-        let calendar_dt: CalendarDateTime = dt.into();
-        self.add_property("DUE", &calendar_dt.to_string());
+    /// See [DatePerhapsTime] for info how are different [chrono] types converted automatically.
+    pub fn due<T: Into<DatePerhapsTime>>(&mut self, dt: T) -> &mut Self {
+        self.append_property(dt.into().to_property("DUE"));
         self
     }
 
@@ -150,7 +361,7 @@ impl Todo {
     /// Per [RFC 5545, Section 3.8.2.1](https://tools.ietf.org/html/rfc5545#section-3.8.2.1), this
     /// must be a date-time in UTC format.
     pub fn completed(&mut self, dt: DateTime<Utc>) -> &mut Self {
-        self.add_property("COMPLETED", &CalendarDateTime::Utc(dt).to_string());
+        self.append_property(Property::with_value("COMPLETED", dt));
         self
     }
 
@@ -160,9 +371,114 @@ impl Todo {
         self
     }
 
-    //pub fn repeats<R:Repeater+?Sized>(&mut self, repeat: R) -> &mut Self {
-    //    unimplemented!()
-    //}
+    /// Gets the overall status
+    pub fn get_status(&self) -> Option<TodoStatus> {
+        self.properties().get("STATUS")?.value().parse().ok()
+    }
+
+    /// Gets the PERCENT-COMPLETE
+    pub fn get_percent_complete(&self) -> Option<u8> {
+        integer(self.properties().get("PERCENT-COMPLETE")?)
+    }
+
+    /// Gets the DUE, all day like [`get_start`](Component::get_start)
+    pub fn get_due(&self) -> Option<DatePerhapsTime> {
+        DatePerhapsTime::from_property(self.properties().get("DUE")?)
+    }
+
+    /// Adds a VALARM, relative triggers refer to DTSTART or DUE
+    pub fn alarm(&mut self, alarm: Alarm) -> &mut Self {
+        self.alarms.push(alarm);
+        self
+    }
+}
+
+impl Journal {
+    /// Creates a new Journal.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        Journal {
+            inner: self.inner.done(),
+        }
+    }
+
+    ///  Defines the overall status
+    pub fn status(&mut self, status: JournalStatus) -> &mut Self {
+        self.append_property(status.into());
+        self
+    }
+
+    /// Gets the overall status
+    pub fn get_status(&self) -> Option<JournalStatus> {
+        self.properties().get("STATUS")?.value().parse().ok()
+    }
+
+    /// Adds a DESCRIPTION `Property`, keeping earlier ones
+    ///
+    /// Unlike other components a journal entry may have many descriptions, e.g. one per note
+    /// taken during the day. [`description`](Component::description) replaces them all.
+    pub fn add_description(&mut self, desc: &str) -> &mut Self {
+        self.add_multi_property("DESCRIPTION", desc)
+    }
+}
+
+impl FreeBusy {
+    /// Creates a new FreeBusy.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        FreeBusy {
+            inner: self.inner.done(),
+        }
+    }
+
+    /// Adds a FREEBUSY `Property` listing `periods` of the given type
+    ///
+    /// Nothing is added if there are no periods.
+    pub fn free_busy<I>(&mut self, fb_type: FreeBusyType, periods: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Period>,
+    {
+        let periods = periods.into_iter().collect::<Vec<_>>();
+        if periods.is_empty() {
+            return self;
+        }
+        self.append_property(
+            Property::with_value("FREEBUSY", periods)
+                .append_parameter(fb_type)
+                .done(),
+        )
+    }
+
+    /// All periods of all FREEBUSY `Property`s, in the order they are written
+    ///
+    /// Periods that can not be read are skipped, a missing `FBTYPE` means busy.
+    pub fn periods(&self) -> Vec<(FreeBusyType, Period)> {
+        self.properties()
+            .get_all("FREEBUSY")
+            .iter()
+            .flat_map(|property| {
+                let fb_type = property
+                    .parameter("FBTYPE")
+                    .and_then(|fb_type| fb_type.value().parse().ok())
+                    .unwrap_or_default();
+                property
+                    .value()
+                    .split(',')
+                    .filter_map(move |period| Some((fb_type, period.parse().ok()?)))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
 }
 
 impl Venue {
@@ -236,17 +552,49 @@ pub trait Component {
     fn component_kind() -> &'static str;
 
     /// Allows access to the inner properties map.
-    fn properties(&self) -> &BTreeMap<String, Property>;
+    fn properties(&self) -> &PropertyMap;
+
+    /// Mutable access to the inner properties map
+    fn properties_mut(&mut self) -> &mut PropertyMap;
+
+    /// Read-only access to the nested VALARMs, only events and todos carry any
+    fn alarms(&self) -> &[Alarm] {
+        &[]
+    }
+
+    /// Mutable access to the first `Property` with the given key, if present
+    fn property_mut(&mut self, key: &str) -> Option<&mut Property> {
+        self.properties_mut().get_mut(key)
+    }
 
-    /// Read-only access to `multi_properties`
-    fn multi_properties(&self) -> &Vec<Property>;
+    /// The `Property`s of which there may be many, see [`PropertyMap::multi`]
+    fn multi_properties(&self) -> Vec<&Property> {
+        self.properties().multi().collect()
+    }
+
+    /// Mutable access to `multi_properties`
+    ///
+    /// They are put back into the component, as changed, when the returned value is dropped.
+    fn multi_properties_mut(&mut self) -> MultiPropertiesMut<'_> {
+        MultiPropertiesMut::new(self.properties_mut())
+    }
 
     /// Writes `Component` into a `Writer` using `std::fmt`.
     fn fmt_write<W: fmt::Write>(&self, out: &mut W) -> Result<(), fmt::Error> {
+        self.fmt_write_with(out, &WriteOptions::default())
+    }
+
+    /// Writes `Component` into a `Writer` using `std::fmt`, a missing DTSTAMP and UID are
+    /// filled in as `options` say.
+    fn fmt_write_with<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        options: &WriteOptions,
+    ) -> Result<(), fmt::Error> {
         write_crlf!(out, "BEGIN:{}", Self::component_kind())?;
 
         if !self.properties().contains_key("DTSTAMP") {
-            let now = CalendarDateTime::Utc(Utc::now());
+            let now = CalendarDateTime::Utc(options.timestamp());
             write_crlf!(out, "DTSTAMP:{}", now)?;
         }
 
@@ -255,11 +603,11 @@ pub trait Component {
         }
 
         if !self.properties().contains_key("UID") {
-            write_crlf!(out, "UID:{}", Uuid::new_v4())?;
+            write_crlf!(out, "UID:{}", options.uid(self))?;
         }
 
-        for property in self.multi_properties() {
-            property.fmt_write(out)?;
+        for alarm in self.alarms() {
+            alarm.fmt_write(out)?;
         }
 
         write_crlf!(out, "END:{}", Self::component_kind())?;
@@ -273,11 +621,46 @@ pub trait Component {
         out_string
     }
 
+    /// Like [`to_string`](Component::to_string), with a missing DTSTAMP and UID filled in as
+    /// `options` say
+    fn to_string_with(&self, options: &WriteOptions) -> String {
+        let mut out_string = String::new();
+        self.fmt_write_with(&mut out_string, options).unwrap();
+        out_string
+    }
+
     /// Append a given `Property`
-    fn append_property(&mut self, property: Property) -> &mut Self;
+    ///
+    /// It replaces one with the same key, unless there may be many such as ATTENDEE.
+    fn append_property(&mut self, property: Property) -> &mut Self {
+        self.properties_mut().insert(property);
+        self
+    }
 
-    /// Adds a `Property` of which there may be many
-    fn append_multi_property(&mut self, property: Property) -> &mut Self;
+    /// Adds a `Property` even if there already is one with the same key
+    fn append_multi_property(&mut self, property: Property) -> &mut Self {
+        self.properties_mut().push(property);
+        self
+    }
+
+    /// Removes the first `Property` with the given key and returns it
+    ///
+    /// See [`PropertyMap::remove`] to remove all of them.
+    fn remove_property(&mut self, key: &str) -> Option<Property> {
+        self.properties_mut().remove_first(key)
+    }
+
+    /// Keeps only the `Property`s for which `predicate` returns `true`
+    fn retain_properties<F: FnMut(&Property) -> bool>(&mut self, predicate: F) -> &mut Self {
+        self.properties_mut().retain(predicate);
+        self
+    }
+
+    /// Keeps only the `multi_properties` for which `predicate` returns `true`
+    fn retain_multi_properties<F: FnMut(&Property) -> bool>(&mut self, predicate: F) -> &mut Self {
+        self.multi_properties_mut().retain(predicate);
+        self
+    }
 
     /// Construct and append a `Property`
     fn add_property(&mut self, key: &str, val: &str) -> &mut Self {
@@ -293,66 +676,74 @@ pub trait Component {
 
     /// Set the DTSTART `Property`
     ///
-    /// See [CalendarDateTime] for info how are different [chrono] types converted automatically.
-    fn starts<T: Into<CalendarDateTime>>(&mut self, dt: T) -> &mut Self {
-        let calendar_dt = dt.into();
-        self.add_property("DTSTART", &calendar_dt.to_string());
+    /// See [DatePerhapsTime] for info how are different [chrono] types converted automatically,
+    /// a [`NaiveDate`] makes it all day.
+    fn starts<T: Into<DatePerhapsTime>>(&mut self, dt: T) -> &mut Self {
+        self.append_property(dt.into().to_property("DTSTART"));
         self
     }
 
     /// Set the DTEND `Property`
     ///
-    /// See [CalendarDateTime] for info how are different [chrono] types converted automatically.
-    fn ends<T: Into<CalendarDateTime>>(&mut self, dt: T) -> &mut Self {
-        let calendar_dt = dt.into();
-        self.add_property("DTEND", &calendar_dt.to_string());
+    /// See [DatePerhapsTime] for info how are different [chrono] types converted automatically,
+    /// a [`NaiveDate`] makes it all day.
+    fn ends<T: Into<DatePerhapsTime>>(&mut self, dt: T) -> &mut Self {
+        self.append_property(dt.into().to_property("DTEND"));
         self
     }
 
+    /// Set the RRULE `Property`
+    ///
+    /// The recurrence set starts at DTSTART, see [RecurrenceRule] on how to describe it.
+    fn repeats(&mut self, rule: RecurrenceRule) -> &mut Self {
+        self.append_property(rule.into())
+    }
+
+    /// Expands the recurrence set described by DTSTART, RRULE, RDATE and EXDATE.
+    ///
+    /// Yields the start of every occurrence from `range_start` (inclusive) up to `range_end`
+    /// (exclusive) in chronological order and in the same form as DTSTART. The range is compared
+    /// against the wall clock value of the occurrences. All day events yield floating date-times
+    /// at midnight. Values that can not be read, such as an invalid RRULE, are ignored.
+    fn occurrences<T: Into<CalendarDateTime>>(&self, range_start: T, range_end: T) -> Occurrences {
+        Occurrences::new(
+            self.properties().values(),
+            range_start.into(),
+            range_end.into(),
+        )
+    }
+
     /// Set the DTSTART `Property`, date only
+    #[allow(deprecated)]
     fn start_date<TZ: TimeZone>(&mut self, date: Date<TZ>) -> &mut Self
     where
         TZ::Offset: fmt::Display,
     {
         // DTSTART
-        self.append_property(
-            Property::new("DTSTART", date.format("%Y%m%d").to_string().as_ref())
-                .append_parameter(ValueType::Date)
-                .done(),
-        );
+        self.append_property(Property::with_value("DTSTART", date.naive_local()));
         self
     }
 
     /// Set the DTEND `Property`, date only
+    #[allow(deprecated)]
     fn end_date<TZ: TimeZone>(&mut self, date: Date<TZ>) -> &mut Self
     where
         TZ::Offset: fmt::Display,
     {
         // DTSTART
-        self.append_property(
-            Property::new("DTEND", date.format("%Y%m%d").to_string().as_ref())
-                .append_parameter(ValueType::Date)
-                .done(),
-        );
+        self.append_property(Property::with_value("DTEND", date.naive_local()));
         self
     }
 
     /// Set the DTSTART `Property`, date only
+    #[allow(deprecated)]
     fn all_day<TZ: TimeZone>(&mut self, date: Date<TZ>) -> &mut Self
     where
         TZ::Offset: fmt::Display,
     {
         // DTSTART
-        self.append_property(
-            Property::new("DTSTART", date.format("%Y%m%d").to_string().as_ref())
-                .append_parameter(ValueType::Date)
-                .done(),
-        )
-        .append_property(
-            Property::new("DTEND", date.format("%Y%m%d").to_string().as_ref())
-                .append_parameter(ValueType::Date)
-                .done(),
-        );
+        self.append_property(Property::with_value("DTSTART", date.naive_local()))
+            .append_property(Property::with_value("DTEND", date.naive_local()));
         self
     }
 
@@ -361,7 +752,7 @@ pub trait Component {
     ///  Ranges from 0 to 10, larger values will be truncated
     fn priority(&mut self, priority: u32) -> &mut Self {
         let priority = ::std::cmp::min(priority, 10);
-        self.add_property("PRIORITY", &priority.to_string());
+        self.append_property(Property::with_value("PRIORITY", priority as i32));
         self
     }
 
@@ -383,11 +774,24 @@ pub trait Component {
         self.add_property("DESCRIPTION", desc)
     }
 
-    ///// Set the description
-    ///// TODO `Attendee` needs to be its own type
-    //fn attendee(&mut self, desc: &str) -> &mut Self {
-    //    self.add_multi_property("ATTENDEE", desc) // multi_properties should be a multimap
-    //}
+    /// Adds an ATTENDEE `Property`
+    fn attendee(&mut self, attendee: Attendee) -> &mut Self {
+        self.append_property(attendee.into())
+    }
+
+    /// All ATTENDEE `Property`s that can be read
+    fn attendees(&self) -> Vec<Attendee> {
+        self.properties()
+            .get_all("ATTENDEE")
+            .iter()
+            .filter_map(|property| Attendee::try_from(property).ok())
+            .collect()
+    }
+
+    /// Set the ORGANIZER `Property`
+    fn organizer(&mut self, organizer: Organizer) -> &mut Self {
+        self.append_property(organizer.into())
+    }
 
     /// Set the LOCATION
     /// 3.8.1.7.  Location
@@ -413,14 +817,78 @@ pub trait Component {
         self
     }
 
+    /// Set a random UID, unless there already is one
+    ///
+    /// Otherwise a new UID is made up every time the component is written, and clients take
+    /// each export for a different component.
+    fn ensure_uid(&mut self) -> &mut Self {
+        if self.properties().contains_key("UID") {
+            return self;
+        }
+        let uid = WriteOptions::default().uid(self);
+        self.uid(&uid)
+    }
+
+    /// Set the DTSTAMP to the current time, unless there already is one
+    fn ensure_timestamp(&mut self) -> &mut Self {
+        if self.properties().contains_key("DTSTAMP") {
+            return self;
+        }
+        let now = CalendarDateTime::Utc(WriteOptions::default().timestamp());
+        self.append_property(now.to_property("DTSTAMP"))
+    }
+
     /// Set the visibility class
     fn class(&mut self, class: Class) -> &mut Self {
         self.append_property(class.into())
     }
+
+    /// Gets the SUMMARY
+    fn get_summary(&self) -> Option<&str> {
+        self.properties().get("SUMMARY")?.as_str()
+    }
+
+    /// Gets the DTSTART
+    ///
+    /// An all day start is a [`DatePerhapsTime::Date`].
+    fn get_start(&self) -> Option<DatePerhapsTime> {
+        DatePerhapsTime::from_property(self.properties().get("DTSTART")?)
+    }
+
+    /// Gets the DTEND, all day like [`get_start`](Component::get_start)
+    fn get_end(&self) -> Option<DatePerhapsTime> {
+        DatePerhapsTime::from_property(self.properties().get("DTEND")?)
+    }
+
+    /// Gets the visibility class
+    fn get_class(&self) -> Option<Class> {
+        self.properties().get("CLASS")?.value().parse().ok()
+    }
+
+    /// Gets the relative priority
+    fn get_priority(&self) -> Option<u32> {
+        integer(self.properties().get("PRIORITY")?)
+    }
+
+    /// Gets the UID
+    fn get_uid(&self) -> Option<&str> {
+        self.properties().get("UID")?.as_str()
+    }
+}
+
+/// Reads a non-negative INTEGER value
+fn integer<T: TryFrom<i32>>(property: &Property) -> Option<T> {
+    match property.typed_value() {
+        Value::Integer(integer) => T::try_from(integer).ok(),
+        _ => None,
+    }
 }
 
 macro_rules! component_impl {
     ($t:ty, $kind:expr) => {
+        component_impl! { $t, $kind, {} }
+    };
+    ($t:ty, $kind:expr, { $($item:item)* }) => {
         impl Component for $t {
             /// Tells you what kind of `Component` this is
             ///
@@ -430,32 +898,34 @@ macro_rules! component_impl {
             }
 
             /// Read-only access to `properties`
-            fn properties(&self) -> &BTreeMap<String, Property> {
+            fn properties(&self) -> &PropertyMap {
                 &self.inner.properties
             }
 
-            /// Read-only access to `multi_properties`
-            fn multi_properties(&self) -> &Vec<Property> {
-                &self.inner.multi_properties
+            /// Mutable access to `properties`
+            fn properties_mut(&mut self) -> &mut PropertyMap {
+                &mut self.inner.properties
             }
 
-            /// Adds a `Property`
-            fn append_property(&mut self, property: Property) -> &mut Self {
-                self.inner
-                    .properties
-                    .insert(property.key().to_owned(), property);
-                self
-            }
-
-            /// Adds a `Property` of which there may be many
-            fn append_multi_property(&mut self, property: Property) -> &mut Self {
-                self.inner.multi_properties.push(property);
-                self
-            }
+            $($item)*
         }
     };
 }
 
-component_impl! { Event, "VEVENT" }
-component_impl! { Todo , "VTODO"}
+pub(crate) use component_impl;
+
+component_impl! { Event, "VEVENT", {
+    /// Read-only access to `alarms`
+    fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+} }
+component_impl! { Todo , "VTODO", {
+    /// Read-only access to `alarms`
+    fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+} }
+component_impl! { Journal , "VJOURNAL"}
+component_impl! { FreeBusy , "VFREEBUSY"}
 component_impl! { Venue , "VVENUE"}
@@ -0,0 +1,445 @@
+//! Email messages carrying scheduling messages as described by iMIP [(RFC 6047)](https://tools.ietf.org/html/rfc6047)
+
+use uuid::Uuid;
+
+use std::{fmt, mem};
+
+use crate::calendar::Calendar;
+
+/// Longest line of base64 allowed in a MIME body
+const BASE64_LINE_LENGTH: usize = 76;
+
+/// Longest encoded-word allowed in a header [(RFC 2047, Section 2)](https://tools.ietf.org/html/rfc2047#section-2)
+const ENCODED_WORD_LENGTH: usize = 75;
+
+/// An iMIP email message wrapping a [`Calendar`]
+///
+/// The calendar is sent as a `text/calendar` part alongside a `text/plain` body, both within a
+/// `multipart/alternative`. If an [`attachment`](Message::attachment) is requested, the
+/// calendar is also attached as an `.ics` file and everything is wrapped in a
+/// `multipart/mixed`. The `method` of the `text/calendar` part is taken from the METHOD
+/// `Property` of the calendar, which iMIP requires.
+///
+/// ```rust
+/// # use icalendar::*;
+/// let mut calendar = Calendar::new();
+/// calendar
+///     .method(Method::Request)
+///     .push(Event::new().summary("Planning").done());
+///
+/// let message = Message::new(&calendar)
+///     .sender("boss@example.com")
+///     .recipient("jane@example.com")
+///     .subject("Invitation: Planning")
+///     .text("You are invited to the planning meeting.")
+///     .attachment("invite.ics")
+///     .done()
+///     .to_string();
+/// assert!(message.contains("Content-Type: text/calendar; charset=UTF-8; method=REQUEST\r\n"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Message {
+    calendar: String,
+    method: Option<String>,
+    headers: Vec<(String, String)>,
+    text: String,
+    attachment: Option<String>,
+    boundary: String,
+}
+
+impl Message {
+    /// Creates a new Message for `calendar`
+    pub fn new(calendar: &Calendar) -> Self {
+        Message {
+            calendar: calendar.to_string(),
+            method: calendar
+                .properties
+                .iter()
+                .rev()
+                .find(|property| property.key() == "METHOD")
+                .map(|property| property.value().into_owned()),
+            headers: Vec::new(),
+            text: String::new(),
+            attachment: None,
+            boundary: Uuid::new_v4().to_simple().to_string(),
+        }
+    }
+
+    /// End of builder pattern.
+    pub fn done(&mut self) -> Self {
+        Message {
+            calendar: mem::take(&mut self.calendar),
+            method: mem::take(&mut self.method),
+            headers: mem::take(&mut self.headers),
+            text: mem::take(&mut self.text),
+            attachment: mem::take(&mut self.attachment),
+            boundary: mem::take(&mut self.boundary),
+        }
+    }
+
+    /// Adds a header, such as `Date` or `Message-ID`, in front of the MIME headers
+    ///
+    /// Values that are not ASCII are encoded as described by RFC 2047.
+    pub fn header(&mut self, name: &str, value: &str) -> &mut Self {
+        self.headers.push((name.to_owned(), encode_word(value)));
+        self
+    }
+
+    /// Set the `From` header
+    ///
+    /// The address may carry a display name, as in `Jane Doe <jane@example.com>`, only the name
+    /// is encoded if it is not ASCII.
+    pub fn sender(&mut self, address: &str) -> &mut Self {
+        self.headers.retain(|(name, _)| name != "From");
+        self.headers
+            .push(("From".to_owned(), encode_address(address)));
+        self
+    }
+
+    /// Adds an address to the `To` header
+    ///
+    /// The address may carry a display name like the one of [`sender`](Message::sender).
+    pub fn recipient(&mut self, address: &str) -> &mut Self {
+        let address = encode_address(address);
+        match self.headers.iter_mut().find(|(name, _)| name == "To") {
+            Some((_, to)) => {
+                to.push_str(", ");
+                to.push_str(&address);
+            }
+            None => self.headers.push(("To".to_owned(), address)),
+        }
+        self
+    }
+
+    /// Set the `Subject` header
+    pub fn subject(&mut self, subject: &str) -> &mut Self {
+        self.headers.retain(|(name, _)| name != "Subject");
+        self.header("Subject", subject)
+    }
+
+    /// Set the `text/plain` body, shown by clients that do not understand iMIP
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        self.text = text.replace("\r\n", "\n").replace('\n', "\r\n");
+        self
+    }
+
+    /// Attaches the calendar as a file with the given name, e.g. `invite.ics`
+    ///
+    /// Names that are not ASCII are encoded as described by RFC 2047.
+    pub fn attachment(&mut self, filename: &str) -> &mut Self {
+        self.attachment = Some(filename.to_owned());
+        self
+    }
+
+    /// Writes the `multipart/alternative` part with the text and the calendar
+    fn fmt_alternative<W: fmt::Write>(&self, out: &mut W, boundary: &str) -> fmt::Result {
+        write_crlf!(
+            out,
+            "Content-Type: multipart/alternative; boundary=\"{}\"",
+            boundary
+        )?;
+        write_crlf!(out)?;
+        write_crlf!(out, "--{}", boundary)?;
+        write_crlf!(out, "Content-Type: text/plain; charset=UTF-8")?;
+        write_crlf!(out, "Content-Transfer-Encoding: 8bit")?;
+        write_crlf!(out)?;
+        write_crlf!(out, "{}", self.text)?;
+        write_crlf!(out, "--{}", boundary)?;
+        match self.method {
+            Some(ref method) => write_crlf!(
+                out,
+                "Content-Type: text/calendar; charset=UTF-8; method={}",
+                method
+            )?,
+            None => write_crlf!(out, "Content-Type: text/calendar; charset=UTF-8")?,
+        }
+        write_crlf!(out, "Content-Transfer-Encoding: 8bit")?;
+        write_crlf!(out)?;
+        out.write_str(&self.calendar)?;
+        write_crlf!(out, "--{}--", boundary)?;
+        Ok(())
+    }
+
+    /// Writes `Message` into a `Writer` using `std::fmt`.
+    fn fmt_write<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        for (name, value) in &self.headers {
+            write_crlf!(out, "{}: {}", name, value)?;
+        }
+        write_crlf!(out, "MIME-Version: 1.0")?;
+        let filename = match self.attachment {
+            Some(ref filename) => quoted_string(&encode_word(filename)),
+            None => return self.fmt_alternative(out, &self.boundary),
+        };
+        let alternative = format!("{}-alt", self.boundary);
+        write_crlf!(
+            out,
+            "Content-Type: multipart/mixed; boundary=\"{}\"",
+            self.boundary
+        )?;
+        write_crlf!(out)?;
+        write_crlf!(out, "--{}", self.boundary)?;
+        self.fmt_alternative(out, &alternative)?;
+        write_crlf!(out, "--{}", self.boundary)?;
+        write_crlf!(out, "Content-Type: application/ics; name={}", filename)?;
+        write_crlf!(
+            out,
+            "Content-Disposition: attachment; filename={}",
+            filename
+        )?;
+        write_crlf!(out, "Content-Transfer-Encoding: base64")?;
+        write_crlf!(out)?;
+        let encoded = base64(self.calendar.as_bytes());
+        for line in encoded.as_bytes().chunks(BASE64_LINE_LENGTH) {
+            // base64 is ASCII, so every chunk is valid UTF-8
+            write_crlf!(out, "{}", String::from_utf8_lossy(line))?;
+        }
+        write_crlf!(out, "--{}--", self.boundary)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_write(f)
+    }
+}
+
+/// Encodes `bytes` as base64 [(RFC 4648, Section 4)](https://tools.ietf.org/html/rfc4648#section-4)
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (u32::from(byte) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(
+                    ALPHABET[((group >> (18 - 6 * i)) & 0x3f) as usize],
+                ));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Encodes a header value as RFC 2047 encoded-words, unless it is plain ASCII
+///
+/// Each encoded-word holds whole characters and is at most [`ENCODED_WORD_LENGTH`] long, the
+/// words are folded onto lines of their own.
+fn encode_word(value: &str) -> String {
+    const PREFIX: &str = "=?UTF-8?B?";
+    const SUFFIX: &str = "?=";
+    if value.is_ascii() && !value.contains(['\r', '\n']) {
+        return value.to_owned();
+    }
+    // every 3 bytes take 4 characters of base64
+    let max_bytes = (ENCODED_WORD_LENGTH - PREFIX.len() - SUFFIX.len()) / 4 * 3;
+    let mut words = Vec::new();
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        if index + c.len_utf8() - start > max_bytes {
+            words.push(&value[start..index]);
+            start = index;
+        }
+    }
+    words.push(&value[start..]);
+    words
+        .iter()
+        .map(|word| format!("{}{}{}", PREFIX, base64(word.as_bytes()), SUFFIX))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+/// Characters that can not be part of an unquoted display name [(RFC 5322, Section 3.2.3)](https://tools.ietf.org/html/rfc5322#section-3.2.3)
+const SPECIALS: &[char] = &[
+    '(', ')', '<', '>', '[', ']', ':', ';', '@', '\\', ',', '.', '"',
+];
+
+/// Puts `value` into a quoted-string, escaping `"` and `\`
+fn quoted_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Encodes the display name of an address such as `Jane Doe <jane@example.com>`
+///
+/// A display name that is not ASCII is encoded, one containing specials such as `,` is quoted.
+/// Control characters like line breaks are dropped from the `addr-spec` within the angle
+/// brackets, or the whole address without them, which is kept as it is otherwise.
+fn encode_address(address: &str) -> String {
+    let addr_spec = |addr_spec: &str| addr_spec.replace(char::is_control, "");
+    match address
+        .trim()
+        .strip_suffix('>')
+        .and_then(|rest| rest.rsplit_once('<'))
+    {
+        Some((name, spec)) if !name.trim().is_empty() => {
+            let name = name.trim();
+            let phrase = if !name.is_ascii() || name.contains(char::is_control) {
+                encode_word(name)
+            } else if name.contains(SPECIALS) {
+                quoted_string(name)
+            } else {
+                name.to_owned()
+            };
+            format!("{} <{}>", phrase, addr_spec(spec))
+        }
+        _ => addr_spec(address.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itip::Method;
+    use pretty_assertions::assert_eq;
+
+    fn boundary(message: &str) -> &str {
+        let start = message.find("boundary=\"").unwrap() + "boundary=\"".len();
+        let end = start + message[start..].find('"').unwrap();
+        &message[start..end]
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(
+            encode_word("Ünterhaltung"),
+            "=?UTF-8?B?w5xudGVyaGFsdHVuZw==?="
+        );
+        assert_eq!(encode_word("Planning"), "Planning");
+    }
+
+    #[test]
+    fn long_encoded_words_are_split() {
+        let value = "Überraschungsbesprechung für das ganze Team mit Kaffee und Kuchen";
+        let encoded = encode_word(value);
+        let words: Vec<&str> = encoded.split("\r\n ").collect();
+        assert!(words.len() > 1);
+        assert!(words.iter().all(|word| word.len() <= ENCODED_WORD_LENGTH));
+        // every word holds whole characters, so it is the encoding of a piece of the value
+        let mut rest = value;
+        for word in words {
+            let piece = rest
+                .char_indices()
+                .map(|(index, c)| &rest[..index + c.len_utf8()])
+                .find(|piece| format!("=?UTF-8?B?{}?=", base64(piece.as_bytes())) == word)
+                .unwrap();
+            rest = &rest[piece.len()..];
+        }
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn addresses_keep_their_addr_spec() {
+        let message = Message::new(&Calendar::new())
+            .sender("Jürgen Boss <boss@example.com>")
+            .recipient("Jane Doe <jane@example.com>")
+            .recipient("john@example.com")
+            .done()
+            .to_string();
+        assert!(message.starts_with(
+            "From: =?UTF-8?B?SsO8cmdlbiBCb3Nz?= <boss@example.com>\r\n\
+             To: Jane Doe <jane@example.com>, john@example.com\r\n"
+        ));
+    }
+
+    #[test]
+    fn addresses_can_not_inject_headers() {
+        let message = Message::new(&Calendar::new())
+            .sender("Doe, Jane <jane@example.com>")
+            .recipient("a@example.com\r\nBcc: x@example.com")
+            .recipient("Evil\r\nBcc: y@example.com <b@example.com\r\nBcc: z@example.com>")
+            .recipient("\"Quoted\" Name <c@example.com>")
+            .attachment("in\"vite\r\n.ics")
+            .done()
+            .to_string();
+        assert!(message.starts_with(
+            "From: \"Doe, Jane\" <jane@example.com>\r\n\
+             To: a@example.comBcc: x@example.com, \
+             =?UTF-8?B?RXZpbA0KQmNjOiB5QGV4YW1wbGUuY29t?= <b@example.comBcc: z@example.com>, \
+             \"\\\"Quoted\\\" Name\" <c@example.com>\r\n"
+        ));
+        assert!(!message.contains("\nBcc"));
+        assert!(message.contains("; name=\"=?UTF-8?B?aW4idml0ZQ0KLmljcw==?=\"\r\n"));
+    }
+
+    #[test]
+    fn alternative() {
+        let mut calendar = Calendar::new();
+        calendar.method(Method::Cancel);
+        let message = Message::new(&calendar)
+            .sender("boss@example.com")
+            .recipient("jane@example.com")
+            .recipient("john@example.com")
+            .subject("Cancelled")
+            .text("Sorry\neveryone")
+            .done()
+            .to_string();
+        let boundary = boundary(&message);
+        assert_eq!(
+            message,
+            format!(
+                "From: boss@example.com\r\n\
+                 To: jane@example.com, john@example.com\r\n\
+                 Subject: Cancelled\r\n\
+                 MIME-Version: 1.0\r\n\
+                 Content-Type: multipart/alternative; boundary=\"{b}\"\r\n\
+                 \r\n\
+                 --{b}\r\n\
+                 Content-Type: text/plain; charset=UTF-8\r\n\
+                 Content-Transfer-Encoding: 8bit\r\n\
+                 \r\n\
+                 Sorry\r\n\
+                 everyone\r\n\
+                 --{b}\r\n\
+                 Content-Type: text/calendar; charset=UTF-8; method=CANCEL\r\n\
+                 Content-Transfer-Encoding: 8bit\r\n\
+                 \r\n\
+                 {calendar}\
+                 --{b}--\r\n",
+                b = boundary,
+                calendar = calendar
+            )
+        );
+    }
+
+    #[test]
+    fn attachment() {
+        let mut calendar = Calendar::new();
+        calendar.method(Method::Request);
+        let message = Message::new(&calendar)
+            .attachment("invite.ics")
+            .done()
+            .to_string();
+        let boundary = boundary(&message);
+        assert!(message.contains(&format!(
+            "Content-Type: multipart/mixed; boundary=\"{b}\"\r\n\r\n\
+             --{b}\r\n\
+             Content-Type: multipart/alternative; boundary=\"{b}-alt\"\r\n",
+            b = boundary
+        )));
+        assert!(message.contains(&format!(
+            "--{b}-alt--\r\n\
+             --{b}\r\n\
+             Content-Type: application/ics; name=\"invite.ics\"\r\n",
+            b = boundary
+        )));
+        let (_, encoded) = message
+            .split_once("Content-Transfer-Encoding: base64\r\n\r\n")
+            .unwrap();
+        let lines: Vec<&str> = encoded.lines().collect();
+        assert_eq!(lines.last(), Some(&&*format!("--{}--", boundary)));
+        assert!(lines.iter().all(|line| line.len() <= BASE64_LINE_LENGTH));
+        assert_eq!(
+            lines[..lines.len() - 1].concat(),
+            base64(calendar.to_string().as_bytes())
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Scheduling messages as described by iTIP [(RFC 5546)](https://tools.ietf.org/html/rfc5546)
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::attendee::{cal_address, Attendee, ParticipationStatus};
+use crate::calendar::{Calendar, CalendarElement};
+use crate::components::{Component, Event, Todo};
+use crate::parser::ParseError;
+use crate::properties::Property;
+
+/// The iTIP method of a scheduling message, the METHOD `Property` of a [`Calendar`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum Method {
+    /// PUBLISH, no interaction expected
+    Publish,
+    /// REQUEST, an invitation or update sent by the organizer
+    Request,
+    /// REPLY of an attendee to a request
+    Reply,
+    /// ADD instances to a recurring event
+    Add,
+    /// CANCEL an event or some of its attendees
+    Cancel,
+    /// REFRESH, asks the organizer for the latest version
+    Refresh,
+    /// COUNTER, an attendee proposes changes
+    Counter,
+    /// DECLINECOUNTER, the organizer rejects a counter proposal
+    #[cfg_attr(feature = "serde", serde(rename = "DECLINECOUNTER"))]
+    DeclineCounter,
+}
+
+impl Method {
+    fn as_str(self) -> &'static str {
+        match self {
+            Method::Publish => "PUBLISH",
+            Method::Request => "REQUEST",
+            Method::Reply => "REPLY",
+            Method::Add => "ADD",
+            Method::Cancel => "CANCEL",
+            Method::Refresh => "REFRESH",
+            Method::Counter => "COUNTER",
+            Method::DeclineCounter => "DECLINECOUNTER",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Method {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "PUBLISH" => Ok(Method::Publish),
+            "REQUEST" => Ok(Method::Request),
+            "REPLY" => Ok(Method::Reply),
+            "ADD" => Ok(Method::Add),
+            "CANCEL" => Ok(Method::Cancel),
+            "REFRESH" => Ok(Method::Refresh),
+            "COUNTER" => Ok(Method::Counter),
+            "DECLINECOUNTER" => Ok(Method::DeclineCounter),
+            _ => Err(ParseError::new(format!("unknown METHOD {:?}", s))),
+        }
+    }
+}
+
+impl From<Method> for Property {
+    fn from(val: Method) -> Self {
+        Property::new("METHOD", val.as_str())
+    }
+}
+
+/// Properties identifying and describing an event or todo that go into a REPLY
+const REPLY_PROPERTIES: &[&str] = &[
+    "UID",
+    "SEQUENCE",
+    "RECURRENCE-ID",
+    "ORGANIZER",
+    "DTSTART",
+    "DTEND",
+    "DURATION",
+    "DUE",
+    "SUMMARY",
+];
+
+/// Whether `property` is the ATTENDEE with the given address
+fn is_attendee(property: &Property, address: &str) -> bool {
+    property.key() == "ATTENDEE" && property.value().eq_ignore_ascii_case(address)
+}
+
+fn reply<C: Component + Default>(
+    component: &C,
+    attendee: &str,
+    status: ParticipationStatus,
+) -> Option<C> {
+    let address = cal_address(attendee);
+    let mut attendee = component
+        .properties()
+        .get_all("ATTENDEE")
+        .iter()
+        .find(|property| is_attendee(property, &address))
+        .and_then(|property| Attendee::try_from(property).ok())?;
+    attendee.participation_status = Some(status);
+    attendee.rsvp = None;
+
+    let mut reply = C::default();
+    for property in component.properties().values() {
+        if REPLY_PROPERTIES.contains(&property.key()) {
+            reply.append_property(property.clone());
+        }
+    }
+    reply.attendee(attendee);
+    Some(reply)
+}
+
+fn cancel<C: Component + Default>(component: &C) -> C {
+    let sequence = component
+        .properties()
+        .get("SEQUENCE")
+        .and_then(|sequence| sequence.value().parse::<u32>().ok())
+        .unwrap_or(0);
+    let mut cancel = C::default();
+    for property in component.properties().values() {
+        if !["DTSTAMP", "SEQUENCE", "STATUS"].contains(&property.key()) {
+            cancel.append_multi_property(property.clone());
+        }
+    }
+    cancel
+        .add_property("SEQUENCE", &(sequence + 1).to_string())
+        .add_property("STATUS", "CANCELLED");
+    cancel
+}
+
+fn counter<C: Component + Default>(component: &C, attendee: &str) -> Option<C> {
+    let address = cal_address(attendee);
+    let attendees = component.properties().get_all("ATTENDEE");
+    if !attendees
+        .iter()
+        .any(|property| is_attendee(property, &address))
+    {
+        return None;
+    }
+    let mut counter = C::default();
+    for property in component.properties().values() {
+        if property.key() != "DTSTAMP" {
+            counter.append_multi_property(property.clone());
+        }
+    }
+    Some(counter)
+}
+
+macro_rules! scheduling_impl {
+    ($t:ty) => {
+        impl $t {
+            /// Answers a request on behalf of `attendee`, an email address or `CAL-ADDRESS`.
+            ///
+            /// The reply identifies the component by UID, SEQUENCE and RECURRENCE-ID, keeps its
+            /// ORGANIZER, times and SUMMARY, and only lists `attendee`, with the new
+            /// participation status. Returns `None` if `attendee` was not invited.
+            pub fn reply(&self, attendee: &str, status: ParticipationStatus) -> Option<Self> {
+                reply(self, attendee, status)
+            }
+
+            /// Calls this off, to be sent with [`Method::Cancel`].
+            ///
+            /// The cancellation keeps all properties, has its SEQUENCE incremented and its
+            /// STATUS set to `CANCELLED`. Alarms are left out.
+            pub fn cancel(&self) -> Self {
+                cancel(self)
+            }
+
+            /// Starts a counter proposal of `attendee`, to be sent with [`Method::Counter`].
+            ///
+            /// The proposal is a copy without alarms, change it to what `attendee` suggests.
+            /// Returns `None` if `attendee` was not invited.
+            pub fn counter(&self, attendee: &str) -> Option<Self> {
+                counter(self, attendee)
+            }
+        }
+    };
+}
+
+scheduling_impl! { Event }
+scheduling_impl! { Todo }
+
+impl Calendar {
+    /// Set the METHOD `Property`, replacing an earlier one
+    ///
+    /// Calendars with a method are iTIP scheduling messages, see [`Method`].
+    pub fn method(&mut self, method: Method) -> &mut Self {
+        self.append_property(method.into())
+    }
+
+    /// Answers all events and todos of a request on behalf of `attendee`.
+    ///
+    /// Returns a calendar with [`Method::Reply`] containing the [`reply`](Event::reply) to every
+    /// event and todo `attendee` was invited to, along with the time zones of the request.
+    ///
+    /// ```rust
+    /// # use icalendar::*;
+    /// let request: Calendar = "BEGIN:VCALENDAR\r\n\
+    ///                          METHOD:REQUEST\r\n\
+    ///                          BEGIN:VEVENT\r\n\
+    ///                          UID:meeting@example.com\r\n\
+    ///                          SEQUENCE:1\r\n\
+    ///                          DTSTART:20210301T090000Z\r\n\
+    ///                          SUMMARY:Planning\r\n\
+    ///                          ORGANIZER:mailto:boss@example.com\r\n\
+    ///                          ATTENDEE;RSVP=TRUE:mailto:jane@example.com\r\n\
+    ///                          ATTENDEE;RSVP=TRUE:mailto:john@example.com\r\n\
+    ///                          END:VEVENT\r\n\
+    ///                          END:VCALENDAR\r\n"
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let reply = request.reply("jane@example.com", ParticipationStatus::Accepted);
+    /// let reply = reply.to_string();
+    /// assert!(reply.contains("METHOD:REPLY\r\n"));
+    /// assert!(reply.contains("ATTENDEE;PARTSTAT=ACCEPTED:mailto:jane@example.com\r\n"));
+    /// assert!(!reply.contains("john@example.com"));
+    /// ```
+    pub fn reply(&self, attendee: &str, status: ParticipationStatus) -> Calendar {
+        let mut calendar = self.scheduling_message(Method::Reply);
+        for component in &self.components {
+            match component {
+                CalendarElement::Event(event) => {
+                    calendar.extend(event.reply(attendee, status.clone()));
+                }
+                CalendarElement::Todo(todo) => {
+                    calendar.extend(todo.reply(attendee, status.clone()));
+                }
+                _ => {}
+            }
+        }
+        calendar
+    }
+
+    /// Cancels all events and todos of this calendar.
+    ///
+    /// Returns a calendar with [`Method::Cancel`] containing the [`cancel`](Event::cancel)led
+    /// version of every event and todo, along with the time zones of this calendar.
+    pub fn cancel(&self) -> Calendar {
+        let mut calendar = self.scheduling_message(Method::Cancel);
+        for component in &self.components {
+            match component {
+                CalendarElement::Event(event) => {
+                    calendar.push(event.cancel());
+                }
+                CalendarElement::Todo(todo) => {
+                    calendar.push(todo.cancel());
+                }
+                _ => {}
+            }
+        }
+        calendar
+    }
+
+    /// An empty calendar with the given method and the time zones of this one
+    fn scheduling_message(&self, method: Method) -> Calendar {
+        let mut calendar = Calendar::new();
+        calendar.method(method);
+        calendar.extend(
+            self.components
+                .iter()
+                .filter(|component| matches!(component, CalendarElement::Timezone(_)))
+                .cloned(),
+        );
+        calendar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attendee::Organizer;
+    use pretty_assertions::assert_eq;
+
+    fn invitation() -> Event {
+        Event::new()
+            .uid("meeting@example.com")
+            .add_property("SEQUENCE", "2")
+            .add_property("DTSTAMP", "20210201T000000Z")
+            .summary("Planning")
+            .description("Agenda")
+            .organizer(Organizer::new("boss@example.com"))
+            .attendee(Attendee::new("jane@example.com").rsvp(true).done())
+            .attendee(Attendee::new("john@example.com").rsvp(true).done())
+            .done()
+    }
+
+    #[test]
+    fn method_is_replaced() {
+        let mut calendar = Calendar::new();
+        calendar.method(Method::Request).method(Method::Cancel);
+        let ics = calendar.to_string();
+        assert_eq!(ics.matches("METHOD:").count(), 1);
+        assert!(ics.contains("METHOD:CANCEL\r\n"));
+        assert_eq!("declinecounter".parse(), Ok(Method::DeclineCounter));
+    }
+
+    #[test]
+    fn reply_only_lists_the_attendee() {
+        let event = invitation();
+        let reply = event
+            .reply("mailto:John@example.com", ParticipationStatus::Declined)
+            .unwrap();
+        assert_eq!(reply.properties()["SEQUENCE"].value(), "2");
+        assert_eq!(reply.properties()["SUMMARY"].value(), "Planning");
+        assert!(!reply.properties().contains_key("DESCRIPTION"));
+        assert!(!reply.properties().contains_key("DTSTAMP"));
+        assert_eq!(
+            reply.attendees(),
+            vec![Attendee::new("john@example.com")
+                .participation_status(ParticipationStatus::Declined)
+                .done()]
+        );
+        assert!(event
+            .reply("joe@example.com", ParticipationStatus::Accepted)
+            .is_none());
+
+        // attendees with extended parameter values are invited all the same
+        let event = Event::new()
+            .uid("planning")
+            .append_multi_property(
+                Property::new("ATTENDEE", "mailto:joe@example.com")
+                    .add_parameter("ROLE", "X-OBSERVER")
+                    .add_parameter("PARTSTAT", "X-WAITING")
+                    .done(),
+            )
+            .done();
+        assert_eq!(event.attendees().len(), 1);
+        let reply = event
+            .reply("joe@example.com", ParticipationStatus::Accepted)
+            .unwrap();
+        assert_eq!(
+            reply.attendees()[0].participation_status,
+            Some(ParticipationStatus::Accepted)
+        );
+    }
+
+    #[test]
+    fn cancel_increments_sequence() {
+        let cancelled = invitation().cancel();
+        assert_eq!(cancelled.properties()["SEQUENCE"].value(), "3");
+        assert_eq!(cancelled.properties()["STATUS"].value(), "CANCELLED");
+        assert_eq!(cancelled.properties()["DESCRIPTION"].value(), "Agenda");
+        assert!(!cancelled.properties().contains_key("DTSTAMP"));
+        assert_eq!(cancelled.attendees().len(), 2);
+        assert_eq!(Todo::new().cancel().properties()["SEQUENCE"].value(), "1");
+    }
+
+    #[test]
+    fn counter_proposal() {
+        let event = invitation();
+        let mut counter = event.counter("jane@example.com").unwrap();
+        counter.add_property("DTSTART", "20210302T090000Z");
+        assert_eq!(counter.attendees().len(), 2);
+        assert!(!counter.properties().contains_key("DTSTAMP"));
+        assert!(event.counter("joe@example.com").is_none());
+    }
+}
@@ -0,0 +1,399 @@
+//! Calendars as JSON as described by jCal [(RFC 7265)](https://tools.ietf.org/html/rfc7265)
+
+use serde_json::{Map, Number, Value};
+
+use crate::calendar::Calendar;
+use crate::output::WriteOptions;
+use crate::parser::{build_calendar, ParseError, RawComponent};
+use crate::properties::{default_value_type, Parameter, Property, ValueType, LIST_PARAMETERS};
+use crate::representation::{
+    build_property, from_extended, items, join_recur, recur_parts, to_extended,
+};
+
+/// Properties whose value is structured by `;`
+const STRUCTURED_PROPERTIES: &[&str] = &["GEO", "REQUEST-STATUS"];
+
+/// Parts of a RECUR value holding numbers
+const NUMERIC_RECUR_PARTS: &[&str] = &[
+    "BYHOUR",
+    "BYMINUTE",
+    "BYMONTH",
+    "BYMONTHDAY",
+    "BYSECOND",
+    "BYSETPOS",
+    "BYWEEKNO",
+    "BYYEARDAY",
+    "COUNT",
+    "INTERVAL",
+];
+
+impl Calendar {
+    /// Converts the calendar into jCal, `["vcalendar", [properties], [components]]`
+    ///
+    /// Values are represented as their type demands, e.g. an INTEGER as number, a DATE-TIME
+    /// as `"2021-03-01T08:00:00Z"` and a RECUR as object.
+    ///
+    /// ```rust
+    /// # use icalendar::*;
+    /// let mut calendar = Calendar::new();
+    /// calendar.push(Event::new().summary("Planning").priority(1).done());
+    ///
+    /// let jcal = calendar.to_jcal();
+    /// assert_eq!(jcal[0], "vcalendar");
+    /// let event = &jcal[2][0];
+    /// assert_eq!(event[0], "vevent");
+    /// assert!(event[1]
+    ///     .as_array()
+    ///     .unwrap()
+    ///     .contains(&serde_json::json!(["priority", {}, "integer", 1])));
+    ///
+    /// let parsed = Calendar::from_jcal(&jcal).unwrap();
+    /// assert_eq!(parsed.len(), 1);
+    /// ```
+    pub fn to_jcal(&self) -> Value {
+        self.to_jcal_with(&WriteOptions::default())
+    }
+
+    /// Like [`to_jcal`](Calendar::to_jcal), with a missing DTSTAMP and UID of the components
+    /// filled in as `options` say
+    pub fn to_jcal_with(&self, options: &WriteOptions) -> Value {
+        component_to_jcal(&self.to_raw(options))
+    }
+
+    /// Reads a calendar from jCal
+    ///
+    /// Components are kept or skipped just like [`Calendar::from_str`](std::str::FromStr) does.
+    pub fn from_jcal(jcal: &Value) -> Result<Calendar, ParseError> {
+        let raw = component_from_jcal(jcal)?;
+        if raw.kind != "VCALENDAR" {
+            return Err(ParseError::new(format!(
+                "expected VCALENDAR, found {}",
+                raw.kind
+            )));
+        }
+        Ok(build_calendar(raw))
+    }
+}
+
+fn component_to_jcal(raw: &RawComponent) -> Value {
+    Value::Array(vec![
+        Value::from(raw.kind.to_lowercase()),
+        raw.properties.iter().map(property_to_jcal).collect(),
+        raw.components.iter().map(component_to_jcal).collect(),
+    ])
+}
+
+fn property_to_jcal(property: &Property) -> Value {
+    let value_type = property.value_type();
+    let mut parameters = Map::new();
+    for parameter in property.parameters() {
+        // the type is given by its own element
+        if parameter.key() != "VALUE" {
+            parameters.insert(parameter.key().to_lowercase(), parameter_to_jcal(parameter));
+        }
+    }
+
+    let mut jcal = vec![
+        Value::from(property.key().to_lowercase()),
+        Value::Object(parameters),
+        Value::from(value_type.as_str().to_lowercase()),
+    ];
+    if STRUCTURED_PROPERTIES.contains(&property.key()) {
+        jcal.push(
+            property
+                .value()
+                .split(';')
+                .map(|part| value_to_jcal(value_type, part))
+                .collect(),
+        );
+    } else {
+        jcal.extend(
+            items(property, value_type)
+                .iter()
+                .map(|item| value_to_jcal(value_type, item)),
+        );
+    }
+    Value::Array(jcal)
+}
+
+fn parameter_to_jcal(parameter: &Parameter) -> Value {
+    if LIST_PARAMETERS.contains(&parameter.key()) {
+        parameter.value().split(',').map(Value::from).collect()
+    } else {
+        Value::from(parameter.value())
+    }
+}
+
+/// Converts a single value of the given type, malformed values are kept as strings
+fn value_to_jcal(value_type: ValueType, value: &str) -> Value {
+    let number = match value_type {
+        ValueType::Boolean => return Value::Bool(value.eq_ignore_ascii_case("TRUE")),
+        ValueType::Recur => return recur_to_jcal(value),
+        ValueType::Integer => value.parse::<i64>().ok().map(Number::from),
+        ValueType::Float => value.parse().ok().and_then(Number::from_f64),
+        _ => None,
+    };
+    match number {
+        Some(number) => Value::Number(number),
+        None => Value::from(to_extended(value_type, value)),
+    }
+}
+
+/// `FREQ=WEEKLY;BYDAY=MO,TU` becomes `{"freq": "WEEKLY", "byday": ["MO", "TU"]}`
+fn recur_to_jcal(value: &str) -> Value {
+    let mut rule = Map::new();
+    for (name, items) in recur_parts(value) {
+        let numeric = NUMERIC_RECUR_PARTS.contains(&name.as_str());
+        let mut items: Vec<Value> = items
+            .into_iter()
+            .map(|item| match item.parse::<i64>() {
+                Ok(number) if numeric => Value::from(number),
+                _ => Value::from(item),
+            })
+            .collect();
+        let value = if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Value::Array(items)
+        };
+        rule.insert(name.to_lowercase(), value);
+    }
+    Value::Object(rule)
+}
+
+fn component_from_jcal(jcal: &Value) -> Result<RawComponent, ParseError> {
+    let (name, properties, components) = match jcal.as_array().map(Vec::as_slice) {
+        Some([name, properties, components]) => (name, properties, components),
+        _ => {
+            return Err(ParseError::new(
+                "a jCal component needs a name, properties and sub-components",
+            ))
+        }
+    };
+    let kind = name
+        .as_str()
+        .ok_or_else(|| ParseError::new("the name of a jCal component must be a string"))?;
+
+    let mut raw = RawComponent::new(kind.to_uppercase());
+    raw.properties = properties
+        .as_array()
+        .ok_or_else(|| ParseError::new("the properties of a jCal component must be an array"))?
+        .iter()
+        .map(property_from_jcal)
+        .collect::<Result<_, _>>()?;
+    raw.components = components
+        .as_array()
+        .ok_or_else(|| ParseError::new("the sub-components of a jCal component must be an array"))?
+        .iter()
+        .map(component_from_jcal)
+        .collect::<Result<_, _>>()?;
+    Ok(raw)
+}
+
+fn property_from_jcal(jcal: &Value) -> Result<Property, ParseError> {
+    let (name, parameters, type_name, values) = match jcal.as_array().map(Vec::as_slice) {
+        Some([name, parameters, type_name, values @ ..]) if !values.is_empty() => {
+            (name, parameters, type_name, values)
+        }
+        _ => {
+            return Err(ParseError::new(
+                "a jCal property needs a name, parameters, a type and a value",
+            ))
+        }
+    };
+    let key = name
+        .as_str()
+        .ok_or_else(|| ParseError::new("the name of a jCal property must be a string"))?
+        .to_uppercase();
+    let type_name = type_name
+        .as_str()
+        .ok_or_else(|| ParseError::new("the type of a jCal property must be a string"))?;
+    let value_type = if type_name.eq_ignore_ascii_case("unknown") {
+        default_value_type(&key)
+    } else {
+        type_name.parse()?
+    };
+
+    let mut items = Vec::with_capacity(values.len());
+    for value in values {
+        items.push(match value {
+            Value::Array(parts) => parts
+                .iter()
+                .map(|part| item_from_jcal(value_type, part))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(";"),
+            Value::Object(rule) => recur_from_jcal(rule)?,
+            value => item_from_jcal(value_type, value)?,
+        });
+    }
+
+    let mut property = build_property(&key, value_type, items);
+    let parameters = parameters
+        .as_object()
+        .ok_or_else(|| ParseError::new("the parameters of a jCal property must be an object"))?;
+    for (name, value) in parameters {
+        property.append_parameter(parameter_from_jcal(name, value)?);
+    }
+    Ok(property)
+}
+
+fn parameter_from_jcal(name: &str, value: &Value) -> Result<Parameter, ParseError> {
+    let key = name.to_uppercase();
+    let value = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(scalar_from_jcal)
+            .collect::<Result<Vec<_>, _>>()?
+            .join(","),
+        value => scalar_from_jcal(value)?,
+    };
+    Ok(Parameter::new(&key, &value))
+}
+
+/// Converts a single value of the given type back
+fn item_from_jcal(value_type: ValueType, value: &Value) -> Result<String, ParseError> {
+    match value {
+        Value::Bool(true) => Ok(String::from("TRUE")),
+        Value::Bool(false) => Ok(String::from("FALSE")),
+        value => Ok(from_extended(value_type, &scalar_from_jcal(value)?)),
+    }
+}
+
+fn scalar_from_jcal(value: &Value) -> Result<String, ParseError> {
+    match value {
+        Value::String(string) => Ok(string.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(boolean) => Ok(boolean.to_string()),
+        _ => Err(ParseError::new(format!(
+            "expected a jCal value, found {}",
+            value
+        ))),
+    }
+}
+
+fn recur_from_jcal(rule: &Map<String, Value>) -> Result<String, ParseError> {
+    let mut parts = Vec::with_capacity(rule.len());
+    for (name, value) in rule {
+        let items = match value {
+            Value::Array(items) => items.iter().map(scalar_from_jcal).collect(),
+            item => scalar_from_jcal(item).map(|item| vec![item]),
+        }?;
+        parts.push((name.clone(), items));
+    }
+    Ok(join_recur(parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn round_trip(property: Property) -> Property {
+        property_from_jcal(&property_to_jcal(&property)).unwrap()
+    }
+
+    #[test]
+    fn values_by_type() {
+        let jcal = |key: &str, value: &str| property_to_jcal(&Property::new(key, value));
+        assert_eq!(
+            jcal("DTSTART", "20210301T080000Z"),
+            json!(["dtstart", {}, "date-time", "2021-03-01T08:00:00Z"])
+        );
+        assert_eq!(jcal("PRIORITY", "1"), json!(["priority", {}, "integer", 1]));
+        assert_eq!(
+            jcal("GEO", "37.386013;-122.082932"),
+            json!(["geo", {}, "float", [37.386013, -122.082932]])
+        );
+        assert_eq!(
+            jcal("TZOFFSETFROM", "-013045"),
+            json!(["tzoffsetfrom", {}, "utc-offset", "-01:30:45"])
+        );
+        assert_eq!(
+            jcal("CATEGORIES", "Work,Fun\\, mostly"),
+            json!(["categories", {}, "text", "Work", "Fun, mostly"])
+        );
+        assert_eq!(
+            jcal(
+                "FREEBUSY",
+                "20210301T080000Z/PT1H,20210301T100000Z/20210301T110000Z"
+            ),
+            json!([
+                "freebusy",
+                {},
+                "period",
+                "2021-03-01T08:00:00Z/PT1H",
+                "2021-03-01T10:00:00Z/2021-03-01T11:00:00Z"
+            ])
+        );
+        assert_eq!(
+            jcal("RRULE", "FREQ=WEEKLY;COUNT=5;BYDAY=MO,TU;UNTIL=20211231"),
+            json!(["rrule", {}, "recur", {
+                "freq": "WEEKLY",
+                "count": 5,
+                "byday": ["MO", "TU"],
+                "until": "2021-12-31"
+            }])
+        );
+        assert_eq!(
+            jcal("SUMMARY", "20210301"),
+            json!(["summary", {}, "text", "20210301"])
+        );
+    }
+
+    #[test]
+    fn parameters() {
+        let property = Property::new("DTSTART", "20210301")
+            .append_parameter(ValueType::Date)
+            .add_parameter("X-NOTE", "a;b")
+            .done();
+        assert_eq!(
+            property_to_jcal(&property),
+            json!(["dtstart", {"x-note": "a;b"}, "date", "2021-03-01"])
+        );
+
+        let attendee = Property::new("ATTENDEE", "mailto:jane@example.com")
+            .add_parameter("DELEGATED-TO", "mailto:a@example.com,mailto:b@example.com")
+            .done();
+        assert_eq!(
+            property_to_jcal(&attendee)[1],
+            json!({"delegated-to": ["mailto:a@example.com", "mailto:b@example.com"]})
+        );
+    }
+
+    #[test]
+    fn values_round_trip() {
+        for (key, value) in &[
+            ("DTSTART", "20210301T080000Z"),
+            ("EXDATE", "20210301T080000,20210308T080000"),
+            ("GEO", "37.386013;-122.082932"),
+            ("CATEGORIES", "Work,Fun\\, mostly"),
+            (
+                "RRULE",
+                "FREQ=WEEKLY;BYDAY=MO,TU;COUNT=5;UNTIL=20211231T000000Z",
+            ),
+            ("TRIGGER", "-PT15M"),
+            ("TZOFFSETTO", "-0500"),
+            ("REQUEST-STATUS", "2.0;Success"),
+            ("SUMMARY", "Planning, with a; semicolon"),
+        ] {
+            assert_eq!(round_trip(Property::new(key, value)).value(), *value);
+        }
+
+        let date = round_trip(
+            Property::new("DTSTART", "20210301")
+                .append_parameter(ValueType::Date)
+                .done(),
+        );
+        assert_eq!(date.value(), "20210301");
+        assert_eq!(date.value_type(), ValueType::Date);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Calendar::from_jcal(&json!(["vcalendar", []])).is_err());
+        assert!(Calendar::from_jcal(&json!(["vevent", [], []])).is_err());
+        assert!(Calendar::from_jcal(&json!(["vcalendar", [["version", {}, "text"]], []])).is_err());
+        assert!(Calendar::from_jcal(&json!(["vcalendar", [["x", {}, "nope", "1"]], []])).is_err());
+        assert!(Calendar::from_jcal(&json!(["vcalendar", [], [["vevent", [], []]]])).is_ok());
+    }
+}
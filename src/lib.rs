@@ -6,7 +6,7 @@
 //!
 //! ## Structure
 //! * `Calendar`s consist of `Components`
-//! * `Component`s are e.g. `Event` or `Todo`
+//! * `Component`s are e.g. `Event`, `Todo` or `Journal`
 //! * `Component`s consist of `Property`s
 //! * `Property`s may have `Parameter`s
 //!
@@ -48,6 +48,72 @@
 //! # }
 //! ```
 //!
+//! ## Parsing
+//!
+//! `Calendar`, `Event`, `Todo`, `Journal` and `Venue` implement [`FromStr`](std::str::FromStr), so
+//! existing iCalendar text can be read back in.
+//!
+//! ```rust
+//! # use icalendar::*;
+//! let calendar: Calendar = "BEGIN:VCALENDAR\r\n\
+//!                           BEGIN:VTODO\r\n\
+//!                           SUMMARY:Buy some milk\r\n\
+//!                           END:VTODO\r\n\
+//!                           END:VCALENDAR\r\n"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(calendar.len(), 1);
+//! ```
+//!
+//! Large files are better read piece by piece with a [`Reader`], which yields the components
+//! one at a time instead of building a whole `Calendar`.
+//!
+//! With the `serde` feature calendars, components and properties implement `Serialize` and
+//! `Deserialize`, so they can be stored in other formats such as JSON directly. Components are
+//! serialized as the list of their properties followed by their alarms or observances, such as
+//! `{"properties": [...], "alarms": [...]}`.
+//!
+//! The `jcal` feature adds [`Calendar::to_jcal`] and [`Calendar::from_jcal`] for the
+//! standardized JSON representation jCal [(RFC 7265)](https://tools.ietf.org/html/rfc7265).
+//! Likewise the `xcal` feature adds [`Calendar::to_xcal`] and [`Calendar::from_xcal`] for the XML
+//! representation xCal [(RFC 6321)](https://tools.ietf.org/html/rfc6321).
+//!
+//! ## Time zones
+//!
+//! Date-times can refer to a time zone by its `TZID`, which a [`Timezone`] component defines.
+//! With the `chrono-tz` feature these definitions can be derived from the IANA time zone
+//! database by [`Calendar::add_timezones`], covering the dates the calendar actually uses.
+//!
+//! ## Scheduling
+//!
+//! Invitations, replies and cancellations are calendars with a [`Method`] as described by
+//! iTIP [(RFC 5546)](https://tools.ietf.org/html/rfc5546). The organizer sends a `REQUEST` with
+//! [`Organizer`] and [`Attendee`]s, attendees answer it with [`Calendar::reply`] or propose
+//! changes with [`Event::counter`], and [`Calendar::cancel`] calls it off again.
+//!
+//! To send these by email as described by iMIP [(RFC 6047)](https://tools.ietf.org/html/rfc6047),
+//! wrap them into a [`Message`].
+//!
+//! ## Breaking API Changes in version 0.11.0
+//!
+//! - [CalendarDateTime] is no longer `Copy`, its new [CalendarDateTime::WithTimezone] variant
+//!   holds the `TZID` as `String`. Call `.clone()` where a date-time was copied before.
+//! - [Component::properties] returns a [PropertyMap] instead of a `BTreeMap<String, Property>`,
+//!   and holds the properties that used to be in [Component::multi_properties] as well.
+//!   `get`, `contains_key`, `keys`, `values`, `len` and `is_empty` keep working as before,
+//!   [PropertyMap::iter] yields the same `(key, property)` pairs as iterating the `BTreeMap` did.
+//!   [Component::multi_properties] and [Component::multi_properties_mut] return the properties
+//!   of which there may be many as before, though no longer as `&Vec<Property>`.
+//! - [Component::starts], [Component::ends] and [Todo::due] take `Into<DatePerhapsTime>`, which
+//!   every type that converts into [CalendarDateTime] does, as well as [chrono::NaiveDate] for
+//!   all day components.
+//! - [Property::value] returns a `Cow<str>` instead of `&str`, as typed values are formatted on
+//!   demand and TEXT is unescaped. Use `&*property.value()` where a `&str` is needed, or
+//!   `.into_owned()` to keep it.
+//! - [Parameter] values are stored without `DQUOTE`s, they are added when writing. Lists such as
+//!   `DELEGATED-TO` hold their addresses separated by commas, pass `mailto:a@example.com` where
+//!   `"mailto:a@example.com"` was passed before.
+//!
 //! ## Breaking API Changes in version 0.7.0
 //!
 //! - [Todo::due] and [Todo::completed] now take their date-time argument by value rather than by
@@ -90,15 +156,51 @@ macro_rules! write_crlf {
     );
 }
 
-//pub mod period;
+mod alarm;
+mod attendee;
 mod calendar;
 mod components;
+mod imip;
+mod itip;
+#[cfg(feature = "jcal")]
+mod jcal;
+mod occurrences;
+mod output;
+mod parser;
+mod period;
 mod properties;
+mod property_map;
+mod reader;
+mod repeats;
+#[cfg(any(feature = "jcal", feature = "xcal"))]
+mod representation;
+#[cfg(feature = "serde")]
+mod shape;
+mod timezone;
+#[cfg(feature = "chrono-tz")]
+mod tzdata;
+mod value;
+#[cfg(feature = "xcal")]
+mod xcal;
 
-//pub mod repeats;
-pub use crate::calendar::Calendar;
-pub use crate::components::{CalendarDateTime, Component, Event, Todo, Venue};
+pub use crate::alarm::{Action, Alarm, Related, Trigger};
+pub use crate::attendee::{
+    Attendee, AttendeeRole, CalendarUserType, Organizer, ParticipationStatus,
+};
+pub use crate::calendar::{Calendar, CalendarElement};
+pub use crate::components::{
+    CalendarDateTime, Component, DatePerhapsTime, Event, FreeBusy, Journal, Todo, Venue,
+};
+pub use crate::imip::Message;
+pub use crate::itip::Method;
+pub use crate::occurrences::Occurrences;
+pub use crate::output::{Clock, UidGenerator, WriteOptions};
+pub use crate::parser::ParseError;
+pub use crate::period::Period;
 pub use crate::properties::{Class, Parameter, Property, ValueType};
-pub use crate::properties::{EventStatus, TodoStatus};
-
-// TODO Calendar TimeZone VTIMEZONE STANDARD DAYLIGHT (see thunderbird exports)
+pub use crate::properties::{EventStatus, FreeBusyType, JournalStatus, TodoStatus};
+pub use crate::property_map::{MultiPropertiesMut, PropertyMap};
+pub use crate::reader::{Components, ParseEvent, Reader};
+pub use crate::repeats::{Freq, Limit, RecurrenceRule, WeekdayNum};
+pub use crate::timezone::{Observance, ObservanceKind, Timezone};
+pub use crate::value::Value;
@@ -0,0 +1,750 @@
+//! Expansion of recurring components into their occurrences
+//!
+//! Every period of a [`RecurrenceRule`] is expanded into a set of date-times as described by the
+//! table in the `repeats` module, the `BYxxx` parts either expanding or limiting that set.
+//! `BYSETPOS` then picks from the set of each period.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use crate::components::CalendarDateTime;
+use crate::properties::Property;
+use crate::repeats::{Freq, Limit, RecurrenceRule, WeekdayNum};
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(28, |last| last.day())
+}
+
+fn days_in_year(year: i32) -> u32 {
+    NaiveDate::from_ymd_opt(year, 12, 31).map_or(365, |last| last.ordinal())
+}
+
+/// The first day of the week containing `date`, weeks starting on `week_start`
+fn week_start_of(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let offset =
+        (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    date - Duration::days(i64::from(offset))
+}
+
+/// Start of week number one, which is the first week containing at least four days of the year
+fn first_week_start(year: i32, week_start: Weekday) -> Option<NaiveDate> {
+    let new_year = NaiveDate::from_ymd_opt(year, 1, 1)?;
+    let start = week_start_of(new_year, week_start);
+    if new_year.signed_duration_since(start).num_days() <= 3 {
+        Some(start)
+    } else {
+        Some(start + Duration::days(7))
+    }
+}
+
+/// Returns the week number of `date` and the number of weeks in its week numbering year
+fn week_number(date: NaiveDate, week_start: Weekday) -> Option<(i64, i64)> {
+    let mut year = date.year();
+    let mut first = first_week_start(year, week_start)?;
+    let next = first_week_start(year + 1, week_start)?;
+    if date >= next {
+        year += 1;
+        first = next;
+    } else if date < first {
+        year -= 1;
+        first = first_week_start(year, week_start)?;
+    }
+    let weeks = first_week_start(year + 1, week_start)?
+        .signed_duration_since(first)
+        .num_days()
+        / 7;
+    let number = date.signed_duration_since(first).num_days() / 7 + 1;
+    Some((number, weeks))
+}
+
+/// Matches a positive or negative (counting from the end) position against `position` of `len`
+fn matches_position(wanted: i64, position: i64, len: i64) -> bool {
+    wanted == position || (wanted < 0 && wanted == position - len - 1)
+}
+
+/// Reads all date-times of a `DTSTART`, `RDATE` or `EXDATE` like property.
+///
+/// Dates are treated as date-times at midnight, periods contribute their start.
+pub(crate) fn date_times(property: &Property) -> Vec<CalendarDateTime> {
+    property
+        .value()
+        .split(',')
+        .filter_map(|value| {
+            let value = value.split('/').next().unwrap_or(value);
+            if value.len() == 8 {
+                NaiveDate::parse_from_str(value, "%Y%m%d")
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(CalendarDateTime::Floating)
+            } else {
+                CalendarDateTime::from_property_value(property, value)
+            }
+        })
+        .collect()
+}
+
+impl RecurrenceRule {
+    /// Fills in the parts that are implied by `DTSTART`, so that every period expands from them.
+    fn normalized(&self, start: NaiveDateTime) -> Self {
+        let mut rule = self.clone();
+        if rule.by_week_no.is_empty()
+            && rule.by_year_day.is_empty()
+            && rule.by_month_day.is_empty()
+            && rule.by_day.is_empty()
+        {
+            match rule.freq {
+                Freq::Yearly => {
+                    if rule.by_month.is_empty() {
+                        rule.by_month = vec![start.month() as u8];
+                    }
+                    rule.by_month_day = vec![start.day() as i8];
+                }
+                Freq::Monthly => rule.by_month_day = vec![start.day() as i8],
+                Freq::Weekly => rule.by_day = vec![WeekdayNum::every(start.weekday())],
+                _ => {}
+            }
+        }
+        if rule.freq > Freq::Hourly && rule.by_hour.is_empty() {
+            rule.by_hour = vec![start.hour() as u8];
+        }
+        if rule.freq > Freq::Minutely && rule.by_minute.is_empty() {
+            rule.by_minute = vec![start.minute() as u8];
+        }
+        if rule.freq > Freq::Secondly && rule.by_second.is_empty() {
+            rule.by_second = vec![start.second() as u8];
+        }
+        rule
+    }
+
+    /// Applies all day based `BYxxx` parts to `date`, either limiting or filtering an expansion.
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let week_start = self.week_start.unwrap_or(Weekday::Mon);
+        let month_len = i64::from(days_in_month(date.year(), date.month()));
+        let year_len = i64::from(days_in_year(date.year()));
+
+        if !self.by_month.is_empty() && !self.by_month.contains(&(date.month() as u8)) {
+            return false;
+        }
+        if !self.by_week_no.is_empty() {
+            match week_number(date, week_start) {
+                Some((number, weeks)) => {
+                    if !self
+                        .by_week_no
+                        .iter()
+                        .any(|&wanted| matches_position(i64::from(wanted), number, weeks))
+                    {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
+        if !self.by_year_day.is_empty()
+            && !self.by_year_day.iter().any(|&wanted| {
+                matches_position(i64::from(wanted), i64::from(date.ordinal()), year_len)
+            })
+        {
+            return false;
+        }
+        if !self.by_month_day.is_empty()
+            && !self.by_month_day.iter().any(|&wanted| {
+                matches_position(i64::from(wanted), i64::from(date.day()), month_len)
+            })
+        {
+            return false;
+        }
+        if !self.by_day.is_empty() {
+            // ordinals count within the month for MONTHLY rules and YEARLY rules with BYMONTH
+            let (position, len) = if self.freq == Freq::Monthly || !self.by_month.is_empty() {
+                (i64::from(date.day()), month_len)
+            } else {
+                (i64::from(date.ordinal()), year_len)
+            };
+            let ordinals_apply = self.freq == Freq::Monthly || self.freq == Freq::Yearly;
+            return self.by_day.iter().any(|day| {
+                day.weekday == date.weekday()
+                    && match day.ordinal {
+                        Some(n) if ordinals_apply => {
+                            let n = i64::from(n);
+                            if n > 0 {
+                                n == (position - 1) / 7 + 1
+                            } else {
+                                -n == (len - position) / 7 + 1
+                            }
+                        }
+                        _ => true,
+                    }
+            });
+        }
+        true
+    }
+
+    /// Times of day within a period, the time parts either expand or limit depending on `FREQ`.
+    fn times(&self, anchor: NaiveDateTime) -> Vec<NaiveTime> {
+        let limits = |list: &[u8], value: u32| list.is_empty() || list.contains(&(value as u8));
+        let (hours, minutes, seconds) = match self.freq {
+            Freq::Secondly => {
+                if !limits(&self.by_hour, anchor.hour())
+                    || !limits(&self.by_minute, anchor.minute())
+                    || !limits(&self.by_second, anchor.second())
+                {
+                    return Vec::new();
+                }
+                return vec![anchor.time()];
+            }
+            Freq::Minutely => {
+                if !limits(&self.by_hour, anchor.hour())
+                    || !limits(&self.by_minute, anchor.minute())
+                {
+                    return Vec::new();
+                }
+                (
+                    vec![anchor.hour() as u8],
+                    vec![anchor.minute() as u8],
+                    self.by_second.clone(),
+                )
+            }
+            Freq::Hourly => {
+                if !limits(&self.by_hour, anchor.hour()) {
+                    return Vec::new();
+                }
+                (
+                    vec![anchor.hour() as u8],
+                    self.by_minute.clone(),
+                    self.by_second.clone(),
+                )
+            }
+            _ => (
+                self.by_hour.clone(),
+                self.by_minute.clone(),
+                self.by_second.clone(),
+            ),
+        };
+
+        let mut times = Vec::with_capacity(hours.len() * minutes.len() * seconds.len());
+        for &hour in &hours {
+            for &minute in &minutes {
+                for &second in &seconds {
+                    // invalid times, such as leap seconds, are ignored
+                    if let Some(time) =
+                        NaiveTime::from_hms_opt(hour.into(), minute.into(), second.into())
+                    {
+                        times.push(time);
+                    }
+                }
+            }
+        }
+        times
+    }
+}
+
+/// Iterates over the date-times a `RecurrenceRule` generates after its start, in order.
+///
+/// Works on wall clock time, the caller takes care of the form of the date-times.
+#[derive(Debug)]
+struct RuleIter {
+    rule: RecurrenceRule,
+    start: NaiveDateTime,
+    until: Option<NaiveDateTime>,
+    /// no more occurrences are generated after this
+    bound: NaiveDateTime,
+    period: i64,
+    emitted: u32,
+    buffer: VecDeque<NaiveDateTime>,
+    done: bool,
+}
+
+impl RuleIter {
+    fn new(rule: &RecurrenceRule, start: NaiveDateTime, bound: NaiveDateTime) -> Self {
+        let until = match &rule.limit {
+            Some(Limit::Until(until)) => Some(until.naive()),
+            Some(Limit::UntilDate(until)) => until.and_hms_opt(23, 59, 59),
+            _ => None,
+        };
+        RuleIter {
+            rule: rule.normalized(start),
+            start,
+            until,
+            bound,
+            period: 0,
+            // DTSTART always counts as the first occurrence
+            emitted: 1,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Skips periods that end before `instant`, which is only possible without a `COUNT`.
+    fn skip_before(&mut self, instant: NaiveDateTime) {
+        if let Some(Limit::Count(_)) = self.rule.limit {
+            return;
+        }
+        let (start, interval) = (self.start, i64::from(self.rule.interval));
+        let elapsed = instant.signed_duration_since(start);
+        let units = match self.rule.freq {
+            Freq::Yearly => i64::from(instant.year() - start.year()),
+            Freq::Monthly => {
+                i64::from(instant.year() - start.year()) * 12 + i64::from(instant.month())
+                    - i64::from(start.month())
+            }
+            Freq::Weekly => elapsed.num_weeks(),
+            Freq::Daily => elapsed.num_days(),
+            Freq::Hourly => elapsed.num_hours(),
+            Freq::Minutely => elapsed.num_minutes(),
+            Freq::Secondly => elapsed.num_seconds(),
+        };
+        self.period = self.period.max(units / interval - 1);
+    }
+
+    /// Returns the first instant of the given period and the days it covers
+    fn period_days(&self, period: i64) -> Option<(NaiveDateTime, Vec<NaiveDate>)> {
+        let n = period.checked_mul(i64::from(self.rule.interval))?;
+        let start = self.start;
+        let midnight = NaiveTime::from_hms_opt(0, 0, 0)?;
+        let days = |first: NaiveDate, count: u32| -> Vec<NaiveDate> {
+            first.iter_days().take(count as usize).collect()
+        };
+
+        Some(match self.rule.freq {
+            Freq::Yearly => {
+                let year = i32::try_from(i64::from(start.year()) + n).ok()?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                (first.and_time(midnight), days(first, days_in_year(year)))
+            }
+            Freq::Monthly => {
+                let index = i64::from(start.year()) * 12 + i64::from(start.month0()) + n;
+                let year = i32::try_from(index.div_euclid(12)).ok()?;
+                let month = index.rem_euclid(12) as u32 + 1;
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                (
+                    first.and_time(midnight),
+                    days(first, days_in_month(year, month)),
+                )
+            }
+            Freq::Weekly => {
+                let week_start = self.rule.week_start.unwrap_or(Weekday::Mon);
+                let first = week_start_of(start.date(), week_start)
+                    .checked_add_signed(Duration::try_weeks(n)?)?;
+                (first.and_time(midnight), days(first, 7))
+            }
+            Freq::Daily => {
+                let day = start.date().checked_add_signed(Duration::try_days(n)?)?;
+                (day.and_time(midnight), vec![day])
+            }
+            Freq::Hourly => {
+                let hour = start.date().and_hms_opt(start.hour(), 0, 0)?;
+                let anchor = hour.checked_add_signed(Duration::try_hours(n)?)?;
+                (anchor, vec![anchor.date()])
+            }
+            Freq::Minutely => {
+                let minute = start.date().and_hms_opt(start.hour(), start.minute(), 0)?;
+                let anchor = minute.checked_add_signed(Duration::try_minutes(n)?)?;
+                (anchor, vec![anchor.date()])
+            }
+            Freq::Secondly => {
+                let anchor = start.checked_add_signed(Duration::try_seconds(n)?)?;
+                (anchor, vec![anchor.date()])
+            }
+        })
+    }
+
+    /// Expands a single period and applies `BYSETPOS`
+    fn expand(&self, anchor: NaiveDateTime, days: Vec<NaiveDate>) -> Vec<NaiveDateTime> {
+        let times = self.rule.times(anchor);
+        let mut set: Vec<NaiveDateTime> = days
+            .into_iter()
+            .filter(|&day| self.rule.matches_day(day))
+            .flat_map(|day| times.iter().map(move |&time| day.and_time(time)))
+            .collect();
+        set.sort();
+        set.dedup();
+
+        if self.rule.by_set_pos.is_empty() {
+            return set;
+        }
+        let len = set.len() as i64;
+        let mut selected: Vec<NaiveDateTime> = self
+            .rule
+            .by_set_pos
+            .iter()
+            .filter_map(|&position| {
+                let position = i64::from(position);
+                let index = if position > 0 {
+                    position - 1
+                } else {
+                    len + position
+                };
+                if (0..len).contains(&index) {
+                    Some(set[index as usize])
+                } else {
+                    None
+                }
+            })
+            .collect();
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+}
+
+impl Iterator for RuleIter {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<NaiveDateTime> {
+        loop {
+            if let Some(next) = self.buffer.pop_front() {
+                let exceeds_until = self.until.is_some_and(|until| next > until);
+                let exceeds_count = match self.rule.limit {
+                    Some(Limit::Count(count)) => self.emitted >= count,
+                    _ => false,
+                };
+                if exceeds_until || exceeds_count {
+                    self.done = true;
+                    self.buffer.clear();
+                    return None;
+                }
+                self.emitted += 1;
+                return Some(next);
+            }
+            if self.done {
+                return None;
+            }
+
+            let (anchor, days) = match self.period_days(self.period) {
+                Some(period) if period.0 <= self.bound => period,
+                _ => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            self.period += 1;
+            let start = self.start;
+            self.buffer.extend(
+                self.expand(anchor, days)
+                    .into_iter()
+                    .filter(|&dt| dt > start),
+            );
+        }
+    }
+}
+
+/// Iterator over the occurrences of a component, see [`Component::occurrences`](crate::Component::occurrences)
+///
+/// Yields the start of every occurrence in chronological order, in the same form as `DTSTART`.
+#[derive(Debug)]
+pub struct Occurrences {
+    start: CalendarDateTime,
+    rule: Option<RuleIter>,
+    next_from_rule: Option<NaiveDateTime>,
+    dates: VecDeque<NaiveDateTime>,
+    exdates: Vec<NaiveDateTime>,
+    range_start: NaiveDateTime,
+    range_end: NaiveDateTime,
+    last: Option<NaiveDateTime>,
+}
+
+impl Occurrences {
+    pub(crate) fn new<'a, I>(
+        properties: I,
+        range_start: CalendarDateTime,
+        range_end: CalendarDateTime,
+    ) -> Self
+    where
+        I: IntoIterator<Item = &'a Property>,
+    {
+        let mut start = None;
+        let mut rule = None;
+        let mut dates = Vec::new();
+        let mut exdates = Vec::new();
+
+        for property in properties {
+            match property.key() {
+                "DTSTART" => start = date_times(property).into_iter().next(),
+                "RRULE" => rule = property.value().parse::<RecurrenceRule>().ok(),
+                "RDATE" => dates.extend(date_times(property).iter().map(CalendarDateTime::naive)),
+                "EXDATE" => {
+                    exdates.extend(date_times(property).iter().map(CalendarDateTime::naive))
+                }
+                _ => {}
+            }
+        }
+
+        let (range_start, range_end) = (range_start.naive(), range_end.naive());
+        let (start, rule) = match start {
+            Some(start) => {
+                let naive = start.naive();
+                dates.push(naive);
+                let rule = rule.map(|rule| {
+                    let mut iter = RuleIter::new(&rule, naive, range_end);
+                    iter.skip_before(range_start);
+                    iter
+                });
+                (start, rule)
+            }
+            // without DTSTART there is nothing to recur
+            None => {
+                dates.clear();
+                (CalendarDateTime::Floating(range_start), None)
+            }
+        };
+        dates.sort();
+        exdates.sort();
+
+        Occurrences {
+            start,
+            rule,
+            next_from_rule: None,
+            dates: dates.into(),
+            exdates,
+            range_start,
+            range_end,
+            last: None,
+        }
+    }
+}
+
+impl Iterator for Occurrences {
+    type Item = CalendarDateTime;
+
+    fn next(&mut self) -> Option<CalendarDateTime> {
+        loop {
+            if self.next_from_rule.is_none() {
+                self.next_from_rule = self.rule.as_mut().and_then(Iterator::next);
+            }
+            let next = match (self.dates.front(), self.next_from_rule) {
+                (Some(&date), Some(from_rule)) if date <= from_rule => self.dates.pop_front(),
+                (_, Some(_)) => self.next_from_rule.take(),
+                (Some(_), None) => self.dates.pop_front(),
+                (None, None) => None,
+            }?;
+
+            if next >= self.range_end {
+                self.rule = None;
+                self.dates.clear();
+                return None;
+            }
+            if self.last == Some(next) {
+                continue;
+            }
+            self.last = Some(next);
+            if next < self.range_start || self.exdates.binary_search(&next).is_ok() {
+                continue;
+            }
+            return Some(self.start.with_naive(next));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, Event};
+    use chrono::{TimeZone, Utc};
+
+    fn expand(start: &str, rule: &str, range_end: &str) -> Vec<String> {
+        let event = Event::new()
+            .add_property("DTSTART", start)
+            .add_property("RRULE", rule)
+            .done();
+        event
+            .occurrences(
+                CalendarDateTime::parse(start).unwrap(),
+                CalendarDateTime::parse(range_end).unwrap(),
+            )
+            .map(|occurrence| occurrence.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn daily_count() {
+        let occurrences = expand("19970902T090000", "FREQ=DAILY;COUNT=10", "20000101T000000");
+        assert_eq!(occurrences.len(), 10);
+        assert_eq!(occurrences[0], "19970902T090000");
+        assert_eq!(occurrences[9], "19970911T090000");
+    }
+
+    #[test]
+    fn weekly_until_keeps_utc_form() {
+        let occurrences = expand(
+            "19970902T090000Z",
+            "FREQ=WEEKLY;INTERVAL=2;UNTIL=19971007T000000Z;BYDAY=TU,TH",
+            "20000101T000000Z",
+        );
+        assert_eq!(
+            occurrences,
+            [
+                "19970902T090000Z",
+                "19970904T090000Z",
+                "19970916T090000Z",
+                "19970918T090000Z",
+                "19970930T090000Z",
+                "19971002T090000Z",
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_nth_weekday() {
+        let occurrences = expand(
+            "19970922T090000",
+            "FREQ=MONTHLY;COUNT=6;BYDAY=-2MO",
+            "20000101T000000",
+        );
+        assert_eq!(
+            occurrences,
+            [
+                "19970922T090000",
+                "19971020T090000",
+                "19971117T090000",
+                "19971222T090000",
+                "19980119T090000",
+                "19980216T090000",
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_last_work_day() {
+        let occurrences = expand(
+            "19970930T090000",
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+            "19980301T000000",
+        );
+        assert_eq!(
+            occurrences,
+            [
+                "19970930T090000",
+                "19971031T090000",
+                "19971128T090000",
+                "19971231T090000",
+                "19980130T090000",
+                "19980227T090000",
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_friday_the_thirteenth() {
+        let occurrences = expand(
+            "19980213T090000",
+            "FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13",
+            "20001231T000000",
+        );
+        assert_eq!(
+            occurrences,
+            [
+                "19980213T090000",
+                "19980313T090000",
+                "19981113T090000",
+                "19990813T090000",
+                "20001013T090000",
+            ]
+        );
+    }
+
+    #[test]
+    fn yearly_by_week_number() {
+        let occurrences = expand(
+            "19970512T090000",
+            "FREQ=YEARLY;BYWEEKNO=20;BYDAY=MO",
+            "20000101T000000",
+        );
+        assert_eq!(
+            occurrences,
+            ["19970512T090000", "19980511T090000", "19990517T090000"]
+        );
+    }
+
+    #[test]
+    fn yearly_defaults_to_start_date() {
+        let occurrences = expand("20200229T120000", "FREQ=YEARLY", "20290101T000000");
+        assert_eq!(
+            occurrences,
+            ["20200229T120000", "20240229T120000", "20280229T120000"]
+        );
+    }
+
+    #[test]
+    fn minutely_limited_by_hour() {
+        let occurrences = expand(
+            "19970902T090000",
+            "FREQ=MINUTELY;INTERVAL=20;BYHOUR=9,10,11,12,13,14,15,16",
+            "19970903T000000",
+        );
+        assert_eq!(occurrences.len(), 24);
+        assert_eq!(occurrences[1], "19970902T092000");
+        assert_eq!(occurrences[23], "19970902T164000");
+    }
+
+    #[test]
+    fn range_skips_ahead() {
+        let event = Event::new()
+            .starts(Utc.with_ymd_and_hms(2000, 1, 3, 10, 0, 0).unwrap())
+            .add_property("RRULE", "FREQ=WEEKLY")
+            .done();
+        let occurrences: Vec<_> = event
+            .occurrences(
+                Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2021, 3, 15, 0, 0, 0).unwrap(),
+            )
+            .map(|occurrence| occurrence.to_string())
+            .collect();
+        assert_eq!(occurrences, ["20210301T100000Z", "20210308T100000Z"]);
+    }
+
+    #[test]
+    fn rdate_and_exdate() {
+        let event = Event::new()
+            .add_property("DTSTART", "20210104T100000")
+            .add_property("RRULE", "FREQ=DAILY;COUNT=5")
+            .add_multi_property("EXDATE", "20210105T100000,20210107T100000")
+            .add_multi_property("RDATE", "20210106T150000")
+            .add_multi_property("RDATE", "20210104T100000")
+            .done();
+        let occurrences: Vec<_> = event
+            .occurrences(
+                CalendarDateTime::parse("20210101T000000").unwrap(),
+                CalendarDateTime::parse("20210201T000000").unwrap(),
+            )
+            .map(|occurrence| occurrence.to_string())
+            .collect();
+        assert_eq!(
+            occurrences,
+            [
+                "20210104T100000",
+                "20210106T100000",
+                "20210106T150000",
+                "20210108T100000",
+            ]
+        );
+    }
+
+    #[test]
+    fn without_rule_only_start() {
+        let event = Event::new()
+            .add_property("DTSTART", "20210104T100000")
+            .done();
+        let range = |s| CalendarDateTime::parse(s).unwrap();
+        assert_eq!(
+            event
+                .occurrences(range("20210101T000000"), range("20210201T000000"))
+                .count(),
+            1
+        );
+        assert_eq!(
+            event
+                .occurrences(range("20210105T000000"), range("20210201T000000"))
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Settings for values that are filled in when components are written
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use std::fmt::{self, Write};
+use std::mem;
+use std::sync::Arc;
+
+use crate::components::Component;
+
+/// Where the DTSTAMP of components without one comes from
+#[derive(Clone, Default)]
+pub enum Clock {
+    /// The current time, read once per component
+    #[default]
+    System,
+    /// Always the given point in time
+    Fixed(DateTime<Utc>),
+    /// Whatever the function returns, called once per component
+    ///
+    /// ```rust
+    /// # use chrono::*;
+    /// # use icalendar::*;
+    /// # use std::sync::Arc;
+    /// let clock = Clock::Custom(Arc::new(|| Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap()));
+    /// let options = WriteOptions::new().clock(clock).done();
+    /// let event = Event::new().summary("Planning").done();
+    /// assert!(event.to_string_with(&options).contains("DTSTAMP:20210301T000000Z\r\n"));
+    /// ```
+    Custom(Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>),
+}
+
+impl Clock {
+    /// The time to stamp a component with
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Fixed(date_time) => *date_time,
+            Clock::Custom(now) => now(),
+        }
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Clock::System => f.write_str("System"),
+            Clock::Fixed(date_time) => f.debug_tuple("Fixed").field(date_time).finish(),
+            Clock::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Custom clocks are only equal if they share the same function.
+impl PartialEq for Clock {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Clock::System, Clock::System) => true,
+            (Clock::Fixed(a), Clock::Fixed(b)) => a == b,
+            (Clock::Custom(a), Clock::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Clock {}
+
+/// How the UID of components without one is made up
+#[derive(Clone, Default)]
+pub enum UidGenerator {
+    /// A random version 4 UUID
+    #[default]
+    Random,
+    /// A hash of the kind and properties of the component, DTSTAMP excluded
+    ///
+    /// Identical components get the same UID.
+    ContentHash,
+    /// Whatever the function returns for the component
+    ///
+    /// The function is given what [`ContentHash`](UidGenerator::ContentHash) hashes, the kind
+    /// and properties of the component as written, DTSTAMP excluded.
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use icalendar::*;
+    /// let uids = UidGenerator::Custom(Arc::new(|content: &str| {
+    ///     format!("{}@example.com", content.len())
+    /// }));
+    /// let options = WriteOptions::new().uids(uids).done();
+    /// let event = Event::new().summary("Planning").done();
+    /// assert!(event.to_string_with(&options).contains("@example.com\r\n"));
+    /// ```
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl fmt::Debug for UidGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UidGenerator::Random => f.write_str("Random"),
+            UidGenerator::ContentHash => f.write_str("ContentHash"),
+            UidGenerator::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Custom generators are only equal if they share the same function.
+impl PartialEq for UidGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (UidGenerator::Random, UidGenerator::Random)
+            | (UidGenerator::ContentHash, UidGenerator::ContentHash) => true,
+            (UidGenerator::Custom(a), UidGenerator::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for UidGenerator {}
+
+/// Decides how DTSTAMP and UID are filled in when a component lacks them
+///
+/// By default components are stamped with the current time and get a random UID, so writing
+/// the same calendar twice gives different results. With a fixed [`Clock`] and
+/// [`UidGenerator::ContentHash`] the output only depends on the calendar itself.
+///
+/// ```rust
+/// # use chrono::*;
+/// # use icalendar::*;
+/// let stamp = Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap();
+/// let options = WriteOptions::deterministic(stamp);
+///
+/// let mut calendar = Calendar::new();
+/// calendar.push(Event::new().summary("Planning").done());
+/// assert_eq!(calendar.to_string_with(&options), calendar.to_string_with(&options));
+/// assert!(calendar.to_string_with(&options).contains("DTSTAMP:20210301T000000Z\r\n"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    clock: Clock,
+    uids: UidGenerator,
+}
+
+impl WriteOptions {
+    /// Creates the default options, the current time and random UIDs
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Options for byte-identical output, stamping with `timestamp` and hashing the content for UIDs
+    pub fn deterministic(timestamp: DateTime<Utc>) -> Self {
+        WriteOptions {
+            clock: Clock::Fixed(timestamp),
+            uids: UidGenerator::ContentHash,
+        }
+    }
+
+    /// End of builder pattern.
+    pub fn done(&mut self) -> Self {
+        mem::take(self)
+    }
+
+    /// Set where DTSTAMPs come from
+    pub fn clock(&mut self, clock: Clock) -> &mut Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set how UIDs are made up
+    pub fn uids(&mut self, uids: UidGenerator) -> &mut Self {
+        self.uids = uids;
+        self
+    }
+
+    /// The DTSTAMP for a component without one
+    pub(crate) fn timestamp(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// The UID for `component`, which has none
+    pub(crate) fn uid<C: Component + ?Sized>(&self, component: &C) -> String {
+        match self.uids {
+            UidGenerator::Random => Uuid::new_v4().to_string(),
+            UidGenerator::ContentHash => {
+                let mut hasher = Fnv::default();
+                write_content(&mut hasher, component).expect("hashing does not fail");
+                format!("{:016x}", hasher.0)
+            }
+            UidGenerator::Custom(ref uid) => {
+                let mut content = String::new();
+                write_content(&mut content, component).expect("writing to a String does not fail");
+                uid(&content)
+            }
+        }
+    }
+}
+
+/// Writes everything that is written of `component` into `out`, but DTSTAMP
+fn write_content<W: Write, C: Component + ?Sized>(out: &mut W, component: &C) -> fmt::Result {
+    write_crlf!(out, "BEGIN:{}", C::component_kind())?;
+    for property in component.properties().values() {
+        if property.key() != "DTSTAMP" {
+            property.fmt_write(out)?;
+        }
+    }
+    for alarm in component.alarms() {
+        alarm.fmt_write(out)?;
+    }
+    Ok(())
+}
+
+/// 64 bit FNV-1a hash of everything written into it
+///
+/// Unlike the hashers of `std` its results are the same across runs, platforms and releases.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Write for Fnv {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Event;
+    use chrono::TimeZone;
+
+    #[test]
+    fn fnv_reference_values() {
+        let hash = |s: &str| {
+            let mut hasher = Fnv::default();
+            hasher.write_str(s).unwrap();
+            hasher.0
+        };
+        assert_eq!(hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn content_hash_ignores_dtstamp() {
+        let options = WriteOptions::new().uids(UidGenerator::ContentHash).done();
+        let event = Event::new().summary("Planning").done();
+        let stamped = Event::new()
+            .summary("Planning")
+            .add_property("DTSTAMP", "20210301T000000Z")
+            .done();
+        let other = Event::new().summary("Review").done();
+        assert_eq!(options.uid(&event), options.uid(&stamped));
+        assert_ne!(options.uid(&event), options.uid(&other));
+        assert_eq!(options.uid(&event).len(), 16);
+    }
+
+    #[test]
+    fn fixed_clock() {
+        let stamp = Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap();
+        let options = WriteOptions::new().clock(Clock::Fixed(stamp)).done();
+        assert_eq!(options.timestamp(), stamp);
+        assert_ne!(options.uid(&Event::new()), options.uid(&Event::new()));
+    }
+
+    #[test]
+    fn custom_clock_and_uids() {
+        let stamp = Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap();
+        let clock = Clock::Custom(Arc::new(move || stamp));
+        let uids = UidGenerator::Custom(Arc::new(|content: &str| {
+            assert!(content.starts_with("BEGIN:VEVENT\r\n"));
+            assert!(!content.contains("DTSTAMP"));
+            format!("{}@example.com", content.lines().count())
+        }));
+        let options = WriteOptions::new().clock(clock.clone()).uids(uids).done();
+        assert_eq!(options.timestamp(), stamp);
+        let event = Event::new()
+            .summary("Planning")
+            .add_property("DTSTAMP", "20210301T000000Z")
+            .done();
+        assert_eq!(options.uid(&event), "2@example.com");
+
+        assert_eq!(clock, clock.clone());
+        assert_ne!(clock, Clock::Custom(Arc::new(move || stamp)));
+        assert_eq!(format!("{:?}", clock), "Custom(..)");
+    }
+}
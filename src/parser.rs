@@ -0,0 +1,483 @@
+//! Reading iCalendar text back into `Calendar`s and `Component`s
+//!
+//! Parsing happens in three steps:
+//!
+//! 1. folded content lines are unfolded by the [`Reader`](crate::Reader) ([RFC 5545, Section 3.1](https://tools.ietf.org/html/rfc5545#section-3.1))
+//! 2. every content line is split into name, `Parameter`s and value
+//! 3. `BEGIN` and `END` lines are used to rebuild the component tree
+
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+use crate::alarm::Alarm;
+use crate::calendar::{Calendar, CalendarElement};
+use crate::components::{Component, Event, FreeBusy, Journal, Todo, Venue};
+use crate::properties::{decode_parameter_value, Parameter, Property, LIST_PARAMETERS};
+use crate::reader::parse_components;
+use crate::timezone::{Observance, ObservanceKind, Timezone};
+
+/// Error returned when iCalendar text can not be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    line: Option<usize>,
+    message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn at_line(line: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+
+    /// Line number (starting at 1) of the offending content line, if the error is tied to one
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Human readable description of what went wrong
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// A component as found in the input, before it is turned into one of the typed components
+///
+/// Other representations such as jCal are converted from and into this tree as well.
+#[derive(Debug)]
+pub(crate) struct RawComponent {
+    pub(crate) kind: String,
+    pub(crate) properties: Vec<Property>,
+    pub(crate) components: Vec<RawComponent>,
+}
+
+impl RawComponent {
+    pub(crate) fn new(kind: String) -> Self {
+        RawComponent {
+            kind,
+            properties: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+}
+
+/// Splits `s` at the first occurrence of any of `delimiters` that is not inside a quoted string.
+fn split_unquoted<'a>(s: &'a str, delimiters: &[char]) -> (&'a str, Option<(char, &'a str)>) {
+    let mut in_quotes = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if !in_quotes && delimiters.contains(&c) => {
+                return (&s[..i], Some((c, &s[i + c.len_utf8()..])));
+            }
+            _ => {}
+        }
+    }
+    (s, None)
+}
+
+/// Strips the surrounding `DQUOTE`s of a parameter value consisting of a single quoted string.
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2
+        && value.starts_with('"')
+        && value.ends_with('"')
+        && !value[1..value.len() - 1].contains('"')
+    {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+/// Strips the `DQUOTE`s around each item of a parameter value holding a list, such as
+/// `"mailto:a@example.com","mailto:b@example.com"`
+fn unquote_list(value: &str) -> String {
+    let mut items = Vec::new();
+    let mut rest = Some((',', value));
+    while let Some((_, remainder)) = rest {
+        let (item, next) = split_unquoted(remainder, &[',']);
+        items.push(unquote(item));
+        rest = next;
+    }
+    items.join(",")
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Parses a single unfolded content line
+///
+/// ```text
+/// contentline = name *(";" param ) ":" value CRLF
+/// ```
+pub(crate) fn parse_content_line(line: &str) -> Result<Property, String> {
+    let (name, mut rest) = split_unquoted(line, &[';', ':']);
+    if !is_valid_name(name) {
+        return Err(format!("invalid property name {:?}", name));
+    }
+
+    let mut parameters = Vec::new();
+    loop {
+        match rest {
+            Some((':', value)) => {
+                let mut property = Property::new(&name.to_uppercase(), value);
+                for parameter in parameters {
+                    property.append_parameter(parameter);
+                }
+                return Ok(property.unescaped());
+            }
+            Some((_, remainder)) => {
+                let (parameter, next) = split_unquoted(remainder, &[';', ':']);
+                let (key, value) = match parameter.find('=') {
+                    Some(index) => (&parameter[..index], &parameter[index + 1..]),
+                    None => return Err(format!("parameter {:?} has no value", parameter)),
+                };
+                if !is_valid_name(key) {
+                    return Err(format!("invalid parameter name {:?}", key));
+                }
+                let key = key.to_uppercase();
+                let value = if LIST_PARAMETERS.contains(&key.as_str()) {
+                    decode_parameter_value(&unquote_list(value))
+                } else {
+                    decode_parameter_value(unquote(value))
+                };
+                parameters.push(Parameter::new(&key, &value));
+                rest = next;
+            }
+            None => return Err(format!("property {:?} has no value", name)),
+        }
+    }
+}
+
+/// Parses `input` expecting exactly one top level component of the given kind
+fn parse_single(input: &str, kind: &str) -> Result<RawComponent, ParseError> {
+    let mut components = parse_components(input.as_bytes())?;
+    match components.len() {
+        1 if components[0].kind == kind => Ok(components.remove(0)),
+        1 => Err(ParseError::new(format!(
+            "expected {}, found {}",
+            kind, components[0].kind
+        ))),
+        0 => Err(ParseError::new(format!("no {} found", kind))),
+        _ => Err(ParseError::new(format!(
+            "expected a single {}, found {} components",
+            kind,
+            components.len()
+        ))),
+    }
+}
+
+/// Rebuilds a `VTIMEZONE` including its `STANDARD` and `DAYLIGHT` observances.
+fn build_timezone(raw: RawComponent) -> Timezone {
+    let mut timezone = Timezone::default();
+    for property in raw.properties {
+        timezone.append_multi_property(property);
+    }
+    for component in raw.components {
+        let kind = match component.kind.as_str() {
+            "STANDARD" => ObservanceKind::Standard,
+            "DAYLIGHT" => ObservanceKind::Daylight,
+            _ => continue,
+        };
+        let mut observance = Observance::new();
+        for property in component.properties {
+            observance.append_multi_property(property);
+        }
+        timezone.append_observance(kind, observance);
+    }
+    timezone
+}
+
+/// Fills a typed component with the properties of a parsed one.
+///
+/// Every property is kept, even one occurring more often than it may, so no value gets lost.
+fn build_component<C: Component + Default>(raw: RawComponent) -> C {
+    let mut component = C::default();
+    for property in raw.properties {
+        component.append_multi_property(property);
+    }
+    component
+}
+
+/// Builds the `VALARM`s among parsed sub-components, others are skipped.
+fn build_alarms(components: Vec<RawComponent>) -> impl Iterator<Item = Alarm> {
+    components
+        .into_iter()
+        .filter(|component| component.kind == "VALARM")
+        .map(|component| {
+            let mut alarm = Alarm::default();
+            for property in component.properties {
+                alarm.append_multi_property(property);
+            }
+            alarm
+        })
+}
+
+fn build_event(mut raw: RawComponent) -> Event {
+    let alarms = build_alarms(mem::take(&mut raw.components));
+    let mut event: Event = build_component(raw);
+    for alarm in alarms {
+        event.alarm(alarm);
+    }
+    event
+}
+
+fn build_todo(mut raw: RawComponent) -> Todo {
+    let alarms = build_alarms(mem::take(&mut raw.components));
+    let mut todo: Todo = build_component(raw);
+    for alarm in alarms {
+        todo.alarm(alarm);
+    }
+    todo
+}
+
+/// Builds the typed component of the kinds a `Calendar` holds, others are `None`.
+pub(crate) fn build_element(raw: RawComponent) -> Option<CalendarElement> {
+    Some(match raw.kind.as_str() {
+        "VEVENT" => build_event(raw).into(),
+        "VTODO" => build_todo(raw).into(),
+        "VJOURNAL" => build_component::<Journal>(raw).into(),
+        "VFREEBUSY" => build_component::<FreeBusy>(raw).into(),
+        "VVENUE" => build_component::<Venue>(raw).into(),
+        "VTIMEZONE" => build_timezone(raw).into(),
+        _ => return None,
+    })
+}
+
+/// Builds a `Calendar` from a parsed `VCALENDAR`, skipping unknown components
+pub(crate) fn build_calendar(raw: RawComponent) -> Calendar {
+    let mut calendar = Calendar::new();
+
+    for property in raw.properties {
+        calendar.append_property(property);
+    }
+
+    calendar.extend(raw.components.into_iter().filter_map(build_element));
+    calendar
+}
+
+impl FromStr for Calendar {
+    type Err = ParseError;
+
+    /// Parses a `VCALENDAR`.
+    ///
+    /// `VEVENT`, `VTODO`, `VJOURNAL`, `VFREEBUSY`, `VVENUE` and `VTIMEZONE` components are
+    /// kept, other components are skipped.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_single(input, "VCALENDAR").map(build_calendar)
+    }
+}
+
+macro_rules! component_from_str {
+    ($t:ty, $build:expr) => {
+        impl FromStr for $t {
+            type Err = ParseError;
+
+            /// Parses a single component, starting with its `BEGIN` and ending with its `END` line.
+            fn from_str(input: &str) -> Result<Self, Self::Err> {
+                parse_single(input, <$t>::component_kind()).map($build)
+            }
+        }
+    };
+}
+
+component_from_str! { Event, build_event }
+component_from_str! { Todo, build_todo }
+component_from_str! { Journal, build_component }
+component_from_str! { FreeBusy, build_component }
+component_from_str! { Venue, build_component }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_line_with_parameters() {
+        let property = parse_content_line("dtstart;VALUE=DATE;x-note=\"a;b:c\":20200315").unwrap();
+        assert_eq!(property.key(), "DTSTART");
+        assert_eq!(property.value(), "20200315");
+
+        let mut out = String::new();
+        property.fmt_write(&mut out).unwrap();
+        assert!(out.contains(";VALUE=DATE"));
+        assert!(out.contains(";X-NOTE=\"a;b:c\""));
+    }
+
+    #[test]
+    fn parameter_values_are_stored_unquoted() {
+        let line = "ATTENDEE;CN=\"Doe, Jane\";DELEGATED-TO=\"mailto:a@example.com\",\"mailto:b@example.com\";\
+                    SENT-BY=\"mailto:c@example.com\":mailto:jane@example.com";
+        let property = parse_content_line(line).unwrap();
+        let value = |key| property.parameter(key).unwrap().value();
+        assert_eq!(value("CN"), "Doe, Jane");
+        assert_eq!(
+            value("DELEGATED-TO"),
+            "mailto:a@example.com,mailto:b@example.com"
+        );
+        assert_eq!(value("SENT-BY"), "mailto:c@example.com");
+
+        let mut out = String::new();
+        property.fmt_write(&mut out).unwrap();
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn content_line_is_unescaped() {
+        let property =
+            parse_content_line("SUMMARY;CN=\"Jane ^'JD^' Doe\":a\\,b\\;c\\nd\\\\").unwrap();
+        assert_eq!(property.value(), "a,b;c\nd\\");
+        assert_eq!(property.parameter("CN").unwrap().value(), "Jane \"JD\" Doe");
+
+        let mut out = String::new();
+        property.fmt_write(&mut out).unwrap();
+        assert_eq!(out, "SUMMARY;CN=Jane ^'JD^' Doe:a\\,b\\;c\\nd\\\\\r\n");
+    }
+
+    #[test]
+    fn content_line_value_may_contain_colons() {
+        let property = parse_content_line("URL:https://example.com/a:b").unwrap();
+        assert_eq!(property.key(), "URL");
+        assert_eq!(property.value(), "https://example.com/a:b");
+    }
+
+    #[test]
+    fn content_line_errors() {
+        assert!(parse_content_line("NOVALUE").is_err());
+        assert!(parse_content_line(":value").is_err());
+        assert!(parse_content_line("KEY;PARAM:value").is_err());
+    }
+
+    #[test]
+    fn mismatched_end() {
+        let err = "BEGIN:VEVENT\nEND:VTODO\n".parse::<Event>().unwrap_err();
+        assert_eq!(err.line(), Some(2));
+    }
+
+    #[test]
+    fn missing_end() {
+        assert!("BEGIN:VCALENDAR\nBEGIN:VTODO\nEND:VTODO\n"
+            .parse::<Calendar>()
+            .is_err());
+    }
+
+    #[test]
+    fn wrong_component_kind() {
+        assert!("BEGIN:VTODO\nEND:VTODO\n".parse::<Event>().is_err());
+    }
+
+    #[test]
+    fn repeated_properties_are_kept() {
+        let event: Event = "BEGIN:VEVENT\r\n\
+                            ATTENDEE:mailto:a@example.com\r\n\
+                            ATTENDEE:mailto:b@example.com\r\n\
+                            END:VEVENT\r\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            event.properties()["ATTENDEE"].value(),
+            "mailto:a@example.com"
+        );
+        let attendees = event.properties().get_all("ATTENDEE");
+        assert_eq!(attendees.len(), 2);
+        assert_eq!(attendees[1].value(), "mailto:b@example.com");
+    }
+
+    #[test]
+    fn calendar_skips_unknown_components() {
+        let calendar: Calendar = "BEGIN:VCALENDAR\n\
+                                  VERSION:2.0\n\
+                                  BEGIN:X-UNKNOWN\n\
+                                  X-FOO:bar\n\
+                                  END:X-UNKNOWN\n\
+                                  BEGIN:VTODO\n\
+                                  SUMMARY:Buy milk\n\
+                                  END:VTODO\n\
+                                  END:VCALENDAR\n"
+            .parse()
+            .unwrap();
+        assert_eq!(calendar.len(), 1);
+    }
+
+    #[test]
+    fn calendar_with_timezone() {
+        let input = "BEGIN:VCALENDAR\r\n\
+                     VERSION:2.0\r\n\
+                     PRODID:ICALENDAR-RS\r\n\
+                     CALSCALE:GREGORIAN\r\n\
+                     BEGIN:VTIMEZONE\r\n\
+                     TZID:Europe/Berlin\r\n\
+                     BEGIN:DAYLIGHT\r\n\
+                     DTSTART:19700329T020000\r\n\
+                     RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3\r\n\
+                     TZNAME:CEST\r\n\
+                     TZOFFSETFROM:+0100\r\n\
+                     TZOFFSETTO:+0200\r\n\
+                     END:DAYLIGHT\r\n\
+                     BEGIN:STANDARD\r\n\
+                     DTSTART:19701025T030000\r\n\
+                     RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10\r\n\
+                     TZNAME:CET\r\n\
+                     TZOFFSETFROM:+0200\r\n\
+                     TZOFFSETTO:+0100\r\n\
+                     END:STANDARD\r\n\
+                     END:VTIMEZONE\r\n\
+                     END:VCALENDAR\r\n";
+        let calendar: Calendar = input.parse().unwrap();
+        match &calendar[0] {
+            CalendarElement::Timezone(timezone) => {
+                assert_eq!(timezone.tzid(), Some("Europe/Berlin"));
+                assert_eq!(timezone.observances().len(), 2);
+                assert_eq!(timezone.observances()[0].kind(), ObservanceKind::Daylight);
+            }
+            other => panic!("expected a time zone, got {:?}", other),
+        }
+        assert_eq!(calendar.to_string(), input);
+    }
+
+    #[test]
+    fn event_with_alarms() {
+        let input = "BEGIN:VEVENT\r\n\
+                     DTSTAMP:20210301T000000Z\r\n\
+                     DTSTART:20210302T090000Z\r\n\
+                     UID:alarmed\r\n\
+                     BEGIN:VALARM\r\n\
+                     ACTION:DISPLAY\r\n\
+                     DESCRIPTION:Soon\r\n\
+                     TRIGGER:-PT15M\r\n\
+                     END:VALARM\r\n\
+                     BEGIN:VALARM\r\n\
+                     ACTION:AUDIO\r\n\
+                     TRIGGER;RELATED=END:PT0S\r\n\
+                     END:VALARM\r\n\
+                     END:VEVENT\r\n";
+        let event: Event = input.parse().unwrap();
+        assert_eq!(event.alarms().len(), 2);
+        assert_eq!(
+            event.alarms()[0].properties()["DESCRIPTION"].value(),
+            "Soon"
+        );
+        assert_eq!(Component::to_string(&event), input);
+    }
+}
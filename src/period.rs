@@ -1,25 +1,236 @@
-//use repeats::*;
-
-//pub trait Period {
-//    fn days(&self) -> Repeats;
-//    fn weeks(&self) -> Repeats;
-//    fn months(&self) -> Repeats;
-//    fn years(&self) -> Repeats;
-//}
-//
-//
-//impl Period for usize {
-//    fn days(&self) -> Repeats {
-//        Repeats::Days(*self)
-//    }
-//    fn weeks(&self) -> Repeats {
-//        Repeats::Weeks(*self)
-//    }
-//    fn months(&self) -> Repeats {
-//        Repeats::Months(*self)
-//    }
-//    fn years(&self) -> Repeats {
-//        Repeats::Years(*self)
-//    }
-//}
-//
+use chrono::{DateTime, Duration, Utc};
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::components::CalendarDateTime;
+use crate::parser::ParseError;
+
+/// `PERIOD` of time in UTC [(RFC 5545, Section 3.3.9 )](https://tools.ietf.org/html/rfc5545#section-3.3.9)
+///
+/// Written in the explicit form, e.g. `19970308T160000Z/19970308T163000Z`. Periods given as
+/// start and duration, e.g. `19970308T160000Z/PT30M`, are parsed as well.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Period {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl Period {
+    /// Creates a new Period, `start` and `end` are swapped if `end` comes first.
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Period {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    /// Creates a new Period lasting `duration` from `start` on.
+    pub fn starting_at(start: DateTime<Utc>, duration: Duration) -> Self {
+        Period::new(start, start + duration)
+    }
+
+    /// Start of the period, inclusive
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    /// End of the period, exclusive
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end
+    }
+
+    /// How long the period lasts
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// The part of this period that lies within `other`, if any
+    pub fn intersection(&self, other: &Period) -> Option<Period> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        if start < end {
+            Some(Period { start, end })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            CalendarDateTime::Utc(self.start),
+            CalendarDateTime::Utc(self.end)
+        )
+    }
+}
+
+impl FromStr for Period {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let utc = |value: &str| match CalendarDateTime::parse(value) {
+            Some(CalendarDateTime::Utc(date_time)) => Ok(date_time),
+            _ => Err(ParseError::new(format!("{:?} is no UTC date-time", value))),
+        };
+        let (start, end) = s
+            .split_once('/')
+            .ok_or_else(|| ParseError::new(format!("{:?} is no period", s)))?;
+        let start = utc(start)?;
+        if end.starts_with(['P', '+', '-']) {
+            let duration = parse_duration(end)
+                .ok_or_else(|| ParseError::new(format!("{:?} is no duration", end)))?;
+            Ok(Period::starting_at(start, duration))
+        } else {
+            Ok(Period::new(start, utc(end)?))
+        }
+    }
+}
+
+/// Sorts `periods` and joins those that overlap or touch.
+pub(crate) fn merge_periods(mut periods: Vec<Period>) -> Vec<Period> {
+    periods.sort();
+    let mut merged: Vec<Period> = Vec::with_capacity(periods.len());
+    for period in periods {
+        match merged.last_mut() {
+            Some(last) if period.start <= last.end => last.end = last.end.max(period.end),
+            _ => merged.push(period),
+        }
+    }
+    merged
+}
+
+/// Formats a `DURATION` value [(RFC 5545, Section 3.3.6 )](https://tools.ietf.org/html/rfc5545#section-3.3.6),
+/// e.g. `-PT15M`, `P1DT12H` or `P2W`
+///
+/// Fractions of seconds are dropped.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.abs();
+    if seconds == 0 {
+        return String::from("PT0S");
+    }
+    if seconds % (7 * 86400) == 0 {
+        return format!("{}P{}W", sign, seconds / (7 * 86400));
+    }
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    let mut out = format!("{}P", sign);
+    if days > 0 {
+        out.push_str(&format!("{}D", days));
+    }
+    if time > 0 {
+        out.push('T');
+        let (hours, minutes, seconds) = (time / 3600, time / 60 % 60, time % 60);
+        if hours > 0 {
+            out.push_str(&format!("{}H", hours));
+        }
+        if minutes > 0 {
+            out.push_str(&format!("{}M", minutes));
+        }
+        if seconds > 0 {
+            out.push_str(&format!("{}S", seconds));
+        }
+    }
+    out
+}
+
+/// Parses a `DURATION` value, the reverse of [`format_duration`]
+pub(crate) fn parse_duration(s: &str) -> Option<Duration> {
+    let (negative, s) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let (mut seconds, mut number, mut in_time, mut any) = (0i64, None::<i64>, false, false);
+    for c in s.strip_prefix('P')?.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            number = Some(number.unwrap_or(0).checked_mul(10)? + i64::from(digit));
+            continue;
+        }
+        if c == 'T' && number.is_none() && !in_time {
+            in_time = true;
+            continue;
+        }
+        let unit = match (c, in_time) {
+            ('W', false) => 7 * 86400,
+            ('D', false) => 86400,
+            ('H', true) => 3600,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(number.take()?.checked_mul(unit)?)?;
+        any = true;
+    }
+    if number.is_some() || !any {
+        return None;
+    }
+    Some(Duration::seconds(if negative { -seconds } else { seconds }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(1997, 3, 8, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::zero()), "PT0S");
+        assert_eq!(format_duration(-Duration::minutes(15)), "-PT15M");
+        assert_eq!(format_duration(Duration::hours(36)), "P1DT12H");
+        assert_eq!(format_duration(Duration::weeks(2)), "P2W");
+        assert_eq!(format_duration(Duration::days(1)), "P1D");
+        assert_eq!(
+            format_duration(Duration::hours(1) + Duration::seconds(5)),
+            "PT1H5S"
+        );
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("-PT15M"), Some(-Duration::minutes(15)));
+        assert_eq!(parse_duration("P1DT12H"), Some(Duration::hours(36)));
+        assert_eq!(parse_duration("+P2W"), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("PT0S"), Some(Duration::zero()));
+        assert_eq!(parse_duration("P15DT5H0M20S"), parse_duration("P15DT5H20S"));
+        assert_eq!(parse_duration("P"), None);
+        assert_eq!(parse_duration("PT5"), None);
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("15M"), None);
+    }
+
+    #[test]
+    fn periods() {
+        let period = Period::new(at(16, 0), at(16, 30));
+        assert_eq!(period.to_string(), "19970308T160000Z/19970308T163000Z");
+        assert_eq!(period.to_string().parse::<Period>(), Ok(period));
+        assert_eq!("19970308T160000Z/PT30M".parse::<Period>(), Ok(period));
+        assert!("19970308T160000/PT30M".parse::<Period>().is_err());
+        assert!("19970308T160000Z".parse::<Period>().is_err());
+    }
+
+    #[test]
+    fn merging() {
+        let merged = merge_periods(vec![
+            Period::new(at(14, 0), at(15, 0)),
+            Period::new(at(9, 0), at(10, 0)),
+            Period::new(at(9, 30), at(11, 0)),
+            Period::new(at(11, 0), at(12, 0)),
+        ]);
+        assert_eq!(
+            merged,
+            vec![
+                Period::new(at(9, 0), at(12, 0)),
+                Period::new(at(14, 0), at(15, 0)),
+            ]
+        );
+    }
+}
@@ -1,9 +1,14 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::fmt::{self, Write};
 use std::mem;
+use std::str::FromStr;
 
-#[derive(Debug)]
+use crate::parser::ParseError;
+use crate::value::Value;
+
+#[derive(Clone, Debug)]
 /// key-value pairs inside of `Property`s
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameter {
     key: String,
     value: String,
@@ -17,43 +22,161 @@ impl Parameter {
             value: val.to_owned(),
         }
     }
+
+    /// Returns the key of this `Parameter`
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the value of this `Parameter`
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
-//type EntryParameters = Vec<Parameter>;
-type EntryParameters = HashMap<String, Parameter>;
+/// Parameters in the order they were added, so they are always written the same way
+type EntryParameters = Vec<Parameter>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 /// key-value pairs inside of `Component`s
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Property {
     key: String,
-    value: String,
+    value: Value,
     parameters: EntryParameters,
 }
 
 impl Property {
     /// Guess what this does :D
+    ///
+    /// `val` is the plain value, TEXT values are escaped when the property is written.
     pub fn new(key: &str, val: &str) -> Self {
         Property {
             key: key.to_owned(),
-            value: val.replace('\n', "\\n"),
-            parameters: HashMap::new(),
+            value: Value::Text(val.to_owned()),
+            parameters: Vec::new(),
         }
     }
 
+    /// Creates a `Property` holding a typed value
+    ///
+    /// The parameters are set as [`set_value`](Self::set_value) describes.
+    ///
+    /// ```rust
+    /// # use icalendar::*;
+    /// let priority = Property::with_value("PRIORITY", 1);
+    /// assert_eq!(priority.value(), "1");
+    /// assert_eq!(priority.typed_value(), Value::Integer(1));
+    /// ```
+    pub fn with_value<V: Into<Value>>(key: &str, value: V) -> Self {
+        let mut property = Property::new(key, "");
+        property.set_value(value);
+        property
+    }
+
     /// Clones the key field.
     pub fn key(&self) -> &str {
         &self.key
     }
 
-    /// Clones the key field.
-    pub fn value(&self) -> &str {
-        &self.value
+    /// Returns the value as written, TEXT unescaped.
+    pub fn value(&self) -> Cow<'_, str> {
+        match self.value.as_str() {
+            Some(text) => Cow::Borrowed(text),
+            None => Cow::Owned(self.value.to_string()),
+        }
+    }
+
+    /// The value if it is given as text, which TZID and the like always are
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        self.value.as_str()
+    }
+
+    /// Returns the value in the form given by [`value_type`](Self::value_type)
+    ///
+    /// Values given as text, as by [`Property::new`] or when parsing, are read according to
+    /// their type, values of list properties such as EXDATE become a [`Value::List`]. Text that
+    /// is no valid value of the type is returned as [`Value::Text`].
+    pub fn typed_value(&self) -> Value {
+        let text = match &self.value {
+            Value::Text(text) => text,
+            typed => return typed.clone(),
+        };
+        let value_type = self.value_type();
+        let tzid = self.parameter("TZID").map(Parameter::value);
+        let parsed = match value_type {
+            ValueType::Text => None,
+            ValueType::Date
+            | ValueType::DateTime
+            | ValueType::Duration
+            | ValueType::Period
+            | ValueType::Time
+                if text.contains(',') =>
+            {
+                text.split(',')
+                    .map(|item| Value::parse(value_type, item, tzid))
+                    .collect::<Option<Vec<_>>>()
+                    .map(Value::List)
+            }
+            _ => Value::parse(value_type, text, tzid),
+        };
+        parsed.unwrap_or_else(|| self.value.clone())
+    }
+
+    /// Returns the `Parameter` with the given key, if present.
+    pub fn parameter(&self, key: &str) -> Option<&Parameter> {
+        self.parameters
+            .iter()
+            .find(|parameter| parameter.key == key)
+    }
+
+    /// Returns the `Parameter`s in the order they were added.
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
     }
 
-    /// Appends a new parameter.
+    /// The type of the value, given by the VALUE parameter or else by the key
+    pub fn value_type(&self) -> ValueType {
+        self.parameter("VALUE")
+            .and_then(|value_type| value_type.value.parse().ok())
+            .unwrap_or_else(|| default_value_type(&self.key))
+    }
+
+    /// Replaces the value
+    ///
+    /// For a typed value the VALUE parameter is added if its type is not the default of the
+    /// key and removed otherwise, a date-time also sets or removes the `TZID` parameter.
+    /// [`Value::Text`] is taken as written, like by [`Property::new`], and keeps the parameters.
+    pub fn set_value<V: Into<Value>>(&mut self, value: V) -> &mut Self {
+        let value = value.into();
+        if !matches!(value, Value::Text(_)) {
+            let value_type = value.value_type();
+            self.parameters.retain(|parameter| parameter.key != "VALUE");
+            if value_type != default_value_type(&self.key) {
+                self.append_parameter(value_type);
+            }
+            if value_type == ValueType::DateTime {
+                self.parameters.retain(|parameter| parameter.key != "TZID");
+                if let Some(tzid) = value.tzid() {
+                    self.add_parameter("TZID", tzid);
+                }
+            }
+        }
+        self.value = value;
+        self
+    }
+
+    /// Appends a new parameter, replacing an earlier one with the same key.
     pub fn append_parameter<I: Into<Parameter>>(&mut self, into_parameter: I) -> &mut Self {
         let parameter = into_parameter.into();
-        self.parameters.insert(parameter.key.to_owned(), parameter);
+        match self
+            .parameters
+            .iter_mut()
+            .find(|existing| existing.key == parameter.key)
+        {
+            Some(existing) => *existing = parameter,
+            None => self.parameters.push(parameter),
+        }
         self
     }
 
@@ -66,29 +189,261 @@ impl Property {
     /// End of Builder Pattern.
     pub fn done(&mut self) -> Self {
         Property {
-            key: mem::replace(&mut self.key, String::new()),
-            value: mem::replace(&mut self.value, String::new()),
-            parameters: mem::replace(&mut self.parameters, HashMap::new()),
+            key: mem::take(&mut self.key),
+            value: mem::take(&mut self.value),
+            parameters: mem::take(&mut self.parameters),
+        }
+    }
+
+    /// Whether the value is TEXT, given by the VALUE parameter or else by the key
+    fn is_text(&self) -> bool {
+        match self.parameter("VALUE") {
+            Some(value_type) => value_type.value.eq_ignore_ascii_case("TEXT"),
+            None => !NON_TEXT_PROPERTIES.contains(&self.key.as_str()),
         }
     }
 
+    /// Turns a value as read from a content line into the plain value.
+    pub(crate) fn unescaped(mut self) -> Self {
+        if let (true, Value::Text(text)) = (self.is_text(), &self.value) {
+            self.value = Value::Text(unescape_text(text, is_list_property(&self.key)));
+        }
+        self
+    }
+
     /// Writes this Property to `out`
+    ///
+    /// TEXT values are escaped [(RFC 5545, Section 3.3.11)](https://tools.ietf.org/html/rfc5545#section-3.3.11),
+    /// the commas separating the items of CATEGORIES and RESOURCES are kept.
     pub fn fmt_write<W: Write>(&self, out: &mut W) -> Result<(), fmt::Error> {
-        // A nice starting capacity for the majority of content lines
-        let mut line = String::with_capacity(150);
+        let mut line = Folded::new(out);
 
-        write!(line, "{}", self.key)?;
-        for &Parameter { ref key, ref value } in self.parameters.values() {
-            write!(line, ";{}={}", key, value)?;
+        line.write_str(&self.key)?;
+        for Parameter { key, value } in &self.parameters {
+            write!(line, ";{}=", key)?;
+            if LIST_PARAMETERS.contains(&key.as_str()) {
+                for (index, item) in value.split(',').enumerate() {
+                    if index > 0 {
+                        line.write_char(',')?;
+                    }
+                    write_parameter_value(&mut line, item, true)?;
+                }
+            } else {
+                let always_quoted = URI_PARAMETERS.contains(&key.as_str());
+                write_parameter_value(&mut line, value, always_quoted)?;
+            }
         }
-        write!(line, ":{}", self.value)?;
-        write_crlf!(out, "{}", fold_line(&line))?;
+        line.write_char(':')?;
+        if self.is_text() {
+            let list = is_list_property(&self.key);
+            write_escaped_text(&mut line, &self.value(), list)?;
+        } else {
+            write!(line, "{}", self.value)?;
+        }
+        write_crlf!(line)?;
         Ok(())
     }
 }
 
+/// Properties whose default value type is not TEXT, or whose TEXT is structured by `;`
+const NON_TEXT_PROPERTIES: &[&str] = &[
+    "ATTACH",
+    "ATTENDEE",
+    "COMPLETED",
+    "CONFERENCE",
+    "CREATED",
+    "DTEND",
+    "DTSTAMP",
+    "DTSTART",
+    "DUE",
+    "DURATION",
+    "EXDATE",
+    "EXRULE",
+    "FREEBUSY",
+    "GEO",
+    "IMAGE",
+    "LAST-MODIFIED",
+    "ORGANIZER",
+    "PERCENT-COMPLETE",
+    "PRIORITY",
+    "RDATE",
+    "RECURRENCE-ID",
+    "REFRESH-INTERVAL",
+    "REPEAT",
+    "REQUEST-STATUS",
+    "RRULE",
+    "SEQUENCE",
+    "SOURCE",
+    "TRIGGER",
+    "TZOFFSETFROM",
+    "TZOFFSETTO",
+    "TZURL",
+    "URL",
+];
+
+/// The value type of a property without VALUE parameter as given by RFC 5545 and RFC 7986
+///
+/// Unknown and experimental properties are TEXT.
+pub(crate) fn default_value_type(key: &str) -> ValueType {
+    match key {
+        "ATTACH" | "CONFERENCE" | "IMAGE" | "SOURCE" | "TZURL" | "URL" => ValueType::Uri,
+        "ATTENDEE" | "ORGANIZER" => ValueType::CalAddress,
+        "COMPLETED" | "CREATED" | "DTEND" | "DTSTAMP" | "DTSTART" | "DUE" | "EXDATE"
+        | "LAST-MODIFIED" | "RDATE" | "RECURRENCE-ID" => ValueType::DateTime,
+        "DURATION" | "REFRESH-INTERVAL" | "TRIGGER" => ValueType::Duration,
+        "FREEBUSY" => ValueType::Period,
+        "GEO" => ValueType::Float,
+        "PERCENT-COMPLETE" | "PRIORITY" | "REPEAT" | "SEQUENCE" => ValueType::Integer,
+        "EXRULE" | "RRULE" => ValueType::Recur,
+        "TZOFFSETFROM" | "TZOFFSETTO" => ValueType::UtcOffset,
+        _ => ValueType::Text,
+    }
+}
+
+/// TEXT properties holding a comma separated list
+const LIST_PROPERTIES: &[&str] = &["CATEGORIES", "RESOURCES"];
+
+/// Whether the TEXT of the property with this key is a comma separated list
+pub(crate) fn is_list_property(key: &str) -> bool {
+    LIST_PROPERTIES.contains(&key)
+}
+
+/// Writes `value`, replacing every character for which `replacement` returns a string
+///
+/// `replacement` also gets to see the next character.
+fn write_replacing<W, F>(out: &mut W, value: &str, replacement: F) -> fmt::Result
+where
+    W: Write,
+    F: Fn(char, Option<char>) -> Option<&'static str>,
+{
+    let mut start = 0;
+    let mut chars = value.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if let Some(replaced) = replacement(c, chars.peek().map(|&(_, next)| next)) {
+            out.write_str(&value[start..i])?;
+            out.write_str(replaced)?;
+            start = i + c.len_utf8();
+        }
+    }
+    out.write_str(&value[start..])
+}
+
+/// Writes an escaped TEXT value, commas are kept as they are in lists.
+fn write_escaped_text<W: Write>(out: &mut W, value: &str, list: bool) -> fmt::Result {
+    write_replacing(out, value, |c, next| match c {
+        // in lists, escaped commas and backslashes are left alone
+        '\\' if !list => Some("\\\\"),
+        ';' => Some("\\;"),
+        ',' if !list => Some("\\,"),
+        '\r' if next == Some('\n') => Some(""),
+        '\n' => Some("\\n"),
+        _ => None,
+    })
+}
+
+/// Reverses [`write_escaped_text`], unknown escapes are kept.
+fn unescape_text(value: &str, list: bool) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') if !list => unescaped.push('\\'),
+            Some(';') => unescaped.push(';'),
+            Some(',') if !list => unescaped.push(','),
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Parameters holding a list of calendar addresses, stored with a comma between the items
+pub(crate) const LIST_PARAMETERS: &[&str] = &["DELEGATED-FROM", "DELEGATED-TO", "MEMBER"];
+
+/// Parameters holding a single URI or calendar address, which RFC 5545 always quotes
+const URI_PARAMETERS: &[&str] = &["ALTREP", "DIR", "SENT-BY"];
+
+/// Writes a parameter value, quoted if it contains delimiters or `always_quoted` is set
+///
+/// Characters a parameter value can not hold are encoded as described by
+/// [RFC 6868](https://tools.ietf.org/html/rfc6868).
+fn write_parameter_value<W: Write>(out: &mut W, value: &str, always_quoted: bool) -> fmt::Result {
+    let quoted = always_quoted || value.contains([':', ';', ',']);
+    if quoted {
+        out.write_char('"')?;
+    }
+    write_replacing(out, value, |c, next| match c {
+        '^' => Some("^^"),
+        '"' => Some("^'"),
+        '\r' if next == Some('\n') => Some(""),
+        '\n' => Some("^n"),
+        _ => None,
+    })?;
+    if quoted {
+        out.write_char('"')?;
+    }
+    Ok(())
+}
+
+/// Reverses the [RFC 6868](https://tools.ietf.org/html/rfc6868) encoding of a parameter value
+pub(crate) fn decode_parameter_value(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '^' {
+            decoded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('^') => decoded.push('^'),
+            Some('\'') => decoded.push('"'),
+            Some('n') => decoded.push('\n'),
+            Some(other) => {
+                decoded.push('^');
+                decoded.push(other);
+            }
+            None => decoded.push('^'),
+        }
+    }
+    decoded
+}
+
+/// Implements the conversions of enums that are the value of a `Property`
+macro_rules! property_enum {
+    ($t:ty, $key:expr, $($variant:path => $value:expr),+ $(,)?) => {
+        impl FromStr for $t {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_uppercase().as_str() {
+                    $($value => Ok($variant),)+
+                    _ => Err(ParseError::new(format!("unknown {} {:?}", $key, s))),
+                }
+            }
+        }
+
+        impl From<$t> for Property {
+            fn from(val: $t) -> Self {
+                Property::new($key, match val {
+                    $($variant => $value,)+
+                })
+            }
+        }
+    };
+}
+
 /// Defines: `Public`, `Private`, `Confidential`
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
 pub enum Class {
     /// Public
     Public,
@@ -98,22 +453,16 @@ pub enum Class {
     Confidential,
 }
 
-impl Into<Property> for Class {
-    fn into(self) -> Property {
-        Property {
-            key: String::from("CLASS"),
-            value: String::from(match self {
-                Class::Public => "PUBLIC",
-                Class::Private => "PRIVATE",
-                Class::Confidential => "CONFIDENTIAL",
-            }),
-            parameters: HashMap::new(),
-        }
-    }
+property_enum! { Class, "CLASS",
+    Class::Public => "PUBLIC",
+    Class::Private => "PRIVATE",
+    Class::Confidential => "CONFIDENTIAL",
 }
 
 /// see 8.3.4. [Value Data Types Registry](https://tools.ietf.org/html/rfc5545#section-8.3.4)
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
 pub enum ValueType {
     /// Binary
     Binary,
@@ -145,32 +494,109 @@ pub enum ValueType {
     UtcOffset,
 }
 
-impl Into<Parameter> for ValueType {
-    fn into(self) -> Parameter {
-        Parameter {
-            key: String::from("VALUE"),
-            value: String::from(match self {
-                ValueType::Binary => "BINARY",
-                ValueType::Boolean => "BOOLEAN",
-                ValueType::CalAddress => "CAL-ADDRESS",
-                ValueType::Date => "DATE",
-                ValueType::DateTime => "DATE-TIME",
-                ValueType::Duration => "DURATION",
-                ValueType::Float => "FLOAT",
-                ValueType::Integer => "INTEGER",
-                ValueType::Period => "PERIOD",
-                ValueType::Recur => "RECUR",
-                ValueType::Text => "TEXT",
-                ValueType::Time => "TIME",
-                ValueType::Uri => "URI",
-                ValueType::UtcOffset => "UTC-OFFSET",
-            }),
+impl ValueType {
+    /// The name of the type as used by the VALUE parameter, e.g. `DATE-TIME`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ValueType::Binary => "BINARY",
+            ValueType::Boolean => "BOOLEAN",
+            ValueType::CalAddress => "CAL-ADDRESS",
+            ValueType::Date => "DATE",
+            ValueType::DateTime => "DATE-TIME",
+            ValueType::Duration => "DURATION",
+            ValueType::Float => "FLOAT",
+            ValueType::Integer => "INTEGER",
+            ValueType::Period => "PERIOD",
+            ValueType::Recur => "RECUR",
+            ValueType::Text => "TEXT",
+            ValueType::Time => "TIME",
+            ValueType::Uri => "URI",
+            ValueType::UtcOffset => "UTC-OFFSET",
+        }
+    }
+}
+
+impl From<ValueType> for Parameter {
+    fn from(val: ValueType) -> Self {
+        Parameter::new("VALUE", val.as_str())
+    }
+}
+
+impl FromStr for ValueType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "BINARY" => Ok(ValueType::Binary),
+            "BOOLEAN" => Ok(ValueType::Boolean),
+            "CAL-ADDRESS" => Ok(ValueType::CalAddress),
+            "DATE" => Ok(ValueType::Date),
+            "DATE-TIME" => Ok(ValueType::DateTime),
+            "DURATION" => Ok(ValueType::Duration),
+            "FLOAT" => Ok(ValueType::Float),
+            "INTEGER" => Ok(ValueType::Integer),
+            "PERIOD" => Ok(ValueType::Period),
+            "RECUR" => Ok(ValueType::Recur),
+            "TEXT" => Ok(ValueType::Text),
+            "TIME" => Ok(ValueType::Time),
+            "URI" => Ok(ValueType::Uri),
+            "UTC-OFFSET" => Ok(ValueType::UtcOffset),
+            _ => Err(ParseError::new(format!("unknown value type {:?}", s))),
+        }
+    }
+}
+
+/// Kind of time described by a FREEBUSY `Property`, its `FBTYPE` parameter
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum FreeBusyType {
+    /// Free for scheduling
+    Free,
+    /// Busy, the default
+    #[default]
+    Busy,
+    /// Unavailable for scheduling, e.g. outside of working hours
+    BusyUnavailable,
+    /// Busy with tentatively scheduled events
+    BusyTentative,
+}
+
+impl FreeBusyType {
+    fn as_str(self) -> &'static str {
+        match self {
+            FreeBusyType::Free => "FREE",
+            FreeBusyType::Busy => "BUSY",
+            FreeBusyType::BusyUnavailable => "BUSY-UNAVAILABLE",
+            FreeBusyType::BusyTentative => "BUSY-TENTATIVE",
+        }
+    }
+}
+
+impl From<FreeBusyType> for Parameter {
+    fn from(val: FreeBusyType) -> Self {
+        Parameter::new("FBTYPE", val.as_str())
+    }
+}
+
+impl FromStr for FreeBusyType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "FREE" => Ok(FreeBusyType::Free),
+            "BUSY" => Ok(FreeBusyType::Busy),
+            "BUSY-UNAVAILABLE" => Ok(FreeBusyType::BusyUnavailable),
+            "BUSY-TENTATIVE" => Ok(FreeBusyType::BusyTentative),
+            _ => Err(ParseError::new(format!("unknown FBTYPE {:?}", s))),
         }
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 /// Encodes the status of an `Event`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
 pub enum EventStatus {
     /// Indicates event is tentative.
     Tentative,
@@ -181,8 +607,10 @@ pub enum EventStatus {
     //Custom(&str)
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 /// Encodes the status of a `Todo`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
 pub enum TodoStatus {
     /// Indicates to-do needs action.
     NeedsAction,
@@ -195,92 +623,80 @@ pub enum TodoStatus {
     //Custom(&str)
 }
 
-//pub enum JournalStatuw{
-//    Draft,
-//    Final,
-//    Cancelled,
-//    Custom(&str)
-//}
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Encodes the status of a `Journal`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum JournalStatus {
+    /// Indicates journal entry is draft.
+    Draft,
+    /// Indicates journal entry is final.
+    Final,
+    /// Indicates journal entry is removed or cancelled.
+    Cancelled,
+}
 
-impl Into<Property> for EventStatus {
-    fn into(self) -> Property {
-        Property {
-            key: String::from("STATUS"),
-            value: String::from(match self {
-                EventStatus::Tentative => "TENTATIVE",
-                EventStatus::Confirmed => "CONFIRMED",
-                EventStatus::Cancelled => "CANCELLED",
-            }),
-            parameters: HashMap::new(),
-        }
-    }
+property_enum! { EventStatus, "STATUS",
+    EventStatus::Tentative => "TENTATIVE",
+    EventStatus::Confirmed => "CONFIRMED",
+    EventStatus::Cancelled => "CANCELLED",
 }
 
-impl Into<Property> for TodoStatus {
-    fn into(self) -> Property {
-        Property {
-            key: String::from("STATUS"),
-            value: String::from(match self {
-                TodoStatus::NeedsAction => "NEEDS-ACTION",
-                TodoStatus::Completed => "COMPLETED",
-                TodoStatus::InProcess => "IN-PROCESS",
-                TodoStatus::Cancelled => "CANCELLED",
-                //TodoStatus::Custom(s)   => "CU",
-            }),
-            parameters: HashMap::new(),
+property_enum! { TodoStatus, "STATUS",
+    TodoStatus::NeedsAction => "NEEDS-ACTION",
+    TodoStatus::Completed => "COMPLETED",
+    TodoStatus::InProcess => "IN-PROCESS",
+    TodoStatus::Cancelled => "CANCELLED",
+}
+
+property_enum! { JournalStatus, "STATUS",
+    JournalStatus::Draft => "DRAFT",
+    JournalStatus::Final => "FINAL",
+    JournalStatus::Cancelled => "CANCELLED",
+}
+
+/// Longest a content line may be, in octets without the line break
+const LINE_LIMIT: usize = 75;
+
+/// Folds content lines as they are written, as described in RFC 5545, Section 3.1
+///
+/// Lines are broken before they exceed [`LINE_LIMIT`] octets, but never within a UTF-8
+/// character, and continued after a single space.
+pub(crate) struct Folded<'a, W: Write> {
+    out: &'a mut W,
+    line_length: usize,
+}
+
+impl<'a, W: Write> Folded<'a, W> {
+    pub(crate) fn new(out: &'a mut W) -> Self {
+        Folded {
+            out,
+            line_length: 0,
         }
     }
 }
 
-//pub enum AttendeeRole {
-//    /// CHAIR           (RFC 5545, Section 3.2.16)
-//    Chair,
-//
-//    /// REQ-PARTICIPANT (RFC 5545, Section 3.2.16)
-//    ReqParticipant,
-//
-//    /// OPT-PARTICIPANT (RFC 5545, Section 3.2.16)
-//    OptParticipant,
-//
-//    /// NON-PARTICIPANT (RFC 5545, Section 3.2.16)
-//    NonParticipant
-//}
-//
-//pub struct Attendee {
-//    cn: String,
-//    role: AttendeeRole,
-//    delegated_from: String,
-//    partstat: String,
-//    sent_by: String,
-//    dir: String,
-//}
-//
-//impl Into<Property> for Attendee {
-//}
-
-// Fold a content line as described in RFC 5545, Section 3.1
-fn fold_line(line: &str) -> String {
-    let limit = 75;
-    let len = line.len();
-    let mut ret = String::with_capacity(len + (len / limit * 3));
-    let mut bytes_remaining = len;
-
-    let mut pos = 0;
-    let mut next_pos = limit;
-    while bytes_remaining > limit {
-        while !line.is_char_boundary(next_pos) {
-            next_pos -= 1;
+impl<W: Write> Write for Folded<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                '\n' => self.line_length = 0,
+                // belongs to the line break that follows
+                '\r' => {}
+                c => {
+                    if self.line_length + c.len_utf8() > LINE_LIMIT {
+                        self.out.write_str(&s[start..i])?;
+                        self.out.write_str("\r\n ")?;
+                        start = i;
+                        self.line_length = 1;
+                    }
+                    self.line_length += c.len_utf8();
+                }
+            }
         }
-        ret.push_str(&line[pos..next_pos]);
-        ret.push_str("\r\n ");
-
-        bytes_remaining -= next_pos - pos;
-        pos = next_pos;
-        next_pos += limit;
+        self.out.write_str(&s[start..])
     }
-
-    ret.push_str(&line[len - bytes_remaining..]);
-    ret
 }
 
 #[cfg(test)]
@@ -288,6 +704,18 @@ mod tests {
     use super::*;
     use std::string::String;
 
+    fn fold_line(line: &str) -> String {
+        let mut out = String::new();
+        Folded::new(&mut out).write_str(line).unwrap();
+        out
+    }
+
+    fn escape_text(value: &str, list: bool) -> String {
+        let mut out = String::new();
+        write_escaped_text(&mut out, value, list).unwrap();
+        out
+    }
+
     #[test]
     fn fold_line_short() {
         let line = String::from("This is a short line");
@@ -306,4 +734,148 @@ mod tests {
         );
         assert_eq!(expected, fold_line(&line));
     }
+
+    #[test]
+    fn fold_line_across_writes() {
+        let mut out = String::new();
+        let mut folded = Folded::new(&mut out);
+        for _ in 0..20 {
+            folded.write_str("0123456789").unwrap();
+        }
+        write_crlf!(folded, "a").unwrap();
+        write_crlf!(folded, "short").unwrap();
+        let lines: Vec<&str> = out.split("\r\n").collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines.iter().all(|line| line.len() <= LINE_LIMIT));
+        assert_eq!(lines[0].len(), LINE_LIMIT);
+        assert!(lines[1].starts_with(' ') && lines[1].len() == LINE_LIMIT);
+        assert_eq!(lines[3..], ["short", ""]);
+        assert_eq!(out.replace("\r\n ", "").len(), 200 + "a\r\nshort\r\n".len());
+    }
+
+    fn written(property: &Property) -> String {
+        let mut out = String::new();
+        property.fmt_write(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let summary = Property::new("SUMMARY", "Lunch; then a walk, maybe\\nap\nor not");
+        assert_eq!(
+            written(&summary),
+            "SUMMARY:Lunch\\; then a walk\\, maybe\\\\nap\\nor not\r\n"
+        );
+        let categories = Property::new("CATEGORIES", "WORK,MEETING;ROOM");
+        assert_eq!(written(&categories), "CATEGORIES:WORK,MEETING\\;ROOM\r\n");
+        let geo = Property::new("GEO", "37.386013;-122.082932");
+        assert_eq!(written(&geo), "GEO:37.386013;-122.082932\r\n");
+        let text = Property::new("X-NOTE", "a,b")
+            .append_parameter(ValueType::Text)
+            .done();
+        assert!(written(&text).ends_with(":a\\,b\r\n"));
+        let uri = Property::new("X-LINK", "https://example.com/?a=1,2")
+            .append_parameter(ValueType::Uri)
+            .done();
+        assert!(written(&uri).ends_with(":https://example.com/?a=1,2\r\n"));
+    }
+
+    #[test]
+    fn text_escaping_round_trip() {
+        for value in &["a\\b; c, d\ne", "\\n", "trailing\\", ""] {
+            assert_eq!(unescape_text(&escape_text(value, false), false), *value);
+        }
+        assert_eq!(unescape_text("a\\Nb\\x", false), "a\nb\\x");
+        assert_eq!(escape_text("a\\,b,c", true), "a\\,b,c");
+        assert_eq!(unescape_text("a\\,b,c\\;d", true), "a\\,b,c;d");
+    }
+
+    #[test]
+    fn parameter_values() {
+        let written = |value: &str| {
+            let mut out = String::new();
+            write_parameter_value(&mut out, value, false).unwrap();
+            out
+        };
+        assert_eq!(written("Jane Doe"), "Jane Doe");
+        assert_eq!(written("Doe, Jane"), "\"Doe, Jane\"");
+        assert_eq!(
+            written("mailto:jane@example.com"),
+            "\"mailto:jane@example.com\""
+        );
+        assert_eq!(written("Jane \"JD\" Doe^"), "Jane ^'JD^' Doe^^");
+        assert_eq!(written("a\nb"), "a^nb");
+        assert_eq!(
+            decode_parameter_value("Jane ^'JD^' Doe^^^x"),
+            "Jane \"JD\" Doe^^x"
+        );
+    }
+    #[test]
+    fn value_types() {
+        assert_eq!(Property::new("SUMMARY", "x").value_type(), ValueType::Text);
+        assert_eq!(Property::new("X-FOO", "x").value_type(), ValueType::Text);
+        assert_eq!(
+            Property::new("DTSTART", "x").value_type(),
+            ValueType::DateTime
+        );
+        let date = Property::new("DTSTART", "20210301")
+            .append_parameter(ValueType::Date)
+            .done();
+        assert_eq!(date.value_type(), ValueType::Date);
+        assert_eq!("utc-offset".parse::<ValueType>(), Ok(ValueType::UtcOffset));
+        assert!("X-NAME".parse::<ValueType>().is_err());
+    }
+
+    #[test]
+    fn typed_values() {
+        use crate::components::CalendarDateTime;
+        use chrono::NaiveDate;
+
+        let start = NaiveDate::from_ymd_opt(2021, 3, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        let dtstart = Property::new("DTSTART", "20210301T080000")
+            .add_parameter("TZID", "Europe/Berlin")
+            .done();
+        assert_eq!(
+            dtstart.typed_value(),
+            Value::DateTime(CalendarDateTime::from((start, "Europe/Berlin")))
+        );
+
+        let exdate = Property::new("EXDATE", "20210301,20210308")
+            .append_parameter(ValueType::Date)
+            .done();
+        assert_eq!(
+            exdate.typed_value(),
+            Value::List(vec![
+                Value::Date(start.date()),
+                Value::Date(NaiveDate::from_ymd_opt(2021, 3, 8).unwrap())
+            ])
+        );
+
+        // kept as text if it is no valid value of its type
+        let invalid = Property::new("PRIORITY", "high");
+        assert_eq!(invalid.typed_value(), Value::Text("high".into()));
+
+        let typed =
+            Property::with_value("DTSTART", CalendarDateTime::from((start, "Europe/Berlin")));
+        assert_eq!(typed.value(), "20210301T080000");
+        assert_eq!(typed.parameter("TZID").unwrap().value(), "Europe/Berlin");
+        let date = Property::with_value("DTSTART", start.date());
+        assert_eq!(date.value_type(), ValueType::Date);
+        let mut out = String::new();
+        date.fmt_write(&mut out).unwrap();
+        assert_eq!(out, "DTSTART;VALUE=DATE:20210301\r\n");
+
+        // the parameters follow the type
+        let mut changed = date;
+        changed.set_value(CalendarDateTime::from((start, "Europe/Berlin")));
+        assert!(changed.parameter("VALUE").is_none());
+        assert_eq!(changed.parameter("TZID").unwrap().value(), "Europe/Berlin");
+        changed.set_value(CalendarDateTime::Floating(start));
+        assert!(changed.parameter("TZID").is_none());
+        changed.set_value("20210301T080000");
+        assert_eq!(changed.typed_value(), Value::DateTime(start.into()));
+    }
 }
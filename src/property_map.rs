@@ -0,0 +1,310 @@
+use std::collections::{btree_map, BTreeMap};
+use std::iter::Flatten;
+use std::mem;
+use std::ops::{Deref, DerefMut, Index};
+
+use crate::properties::Property;
+
+/// Properties that may occur more than once in a component as given by RFC 5545 and RFC 7986
+const REPEATING_PROPERTIES: &[&str] = &[
+    "ATTACH",
+    "ATTENDEE",
+    "CATEGORIES",
+    "COMMENT",
+    "CONFERENCE",
+    "CONTACT",
+    "EXDATE",
+    "FREEBUSY",
+    "IMAGE",
+    "RDATE",
+    "RELATED-TO",
+    "REQUEST-STATUS",
+    "RESOURCES",
+    "TZNAME",
+];
+
+/// Whether a property with `key` may occur more than once in a component
+pub(crate) fn may_repeat(key: &str) -> bool {
+    REPEATING_PROPERTIES.contains(&key)
+}
+
+/// The `Property`s of a component, ordered by key
+///
+/// Properties that may occur more than once, such as ATTENDEE or RDATE, are kept in the order
+/// they were added, any other property replaces an earlier one with the same key.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PropertyMap {
+    entries: BTreeMap<String, Vec<Property>>,
+}
+
+impl PropertyMap {
+    /// Creates an empty `PropertyMap`
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a `Property`, returning the one it replaces
+    ///
+    /// Properties that may repeat are appended and never replace another.
+    pub fn insert(&mut self, property: Property) -> Option<Property> {
+        if may_repeat(property.key()) {
+            self.push(property);
+            return None;
+        }
+        let replaced = self
+            .entries
+            .insert(property.key().to_owned(), vec![property]);
+        replaced.and_then(|mut replaced| replaced.pop())
+    }
+
+    /// Appends a `Property`, even if its key does not usually repeat
+    pub fn push(&mut self, property: Property) {
+        self.entries
+            .entry(property.key().to_owned())
+            .or_default()
+            .push(property);
+    }
+
+    /// The first `Property` with the given key
+    pub fn get(&self, key: &str) -> Option<&Property> {
+        self.get_all(key).first()
+    }
+
+    /// All `Property`s with the given key, in the order they were added
+    pub fn get_all(&self, key: &str) -> &[Property] {
+        self.entries.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Mutable access to the first `Property` with the given key
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Property> {
+        self.entries.get_mut(key)?.first_mut()
+    }
+
+    /// Whether there is a `Property` with the given key
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Removes all `Property`s with the given key and returns them
+    pub fn remove(&mut self, key: &str) -> Vec<Property> {
+        self.entries.remove(key).unwrap_or_default()
+    }
+
+    /// Removes the first `Property` with the given key and returns it
+    pub fn remove_first(&mut self, key: &str) -> Option<Property> {
+        let properties = self.entries.get_mut(key)?;
+        let removed = properties.remove(0);
+        if properties.is_empty() {
+            self.entries.remove(key);
+        }
+        Some(removed)
+    }
+
+    /// The `Property`s of which there may be many
+    ///
+    /// These are all of a key that may repeat, and those following the first one of any other
+    /// key, as added by [`push`](PropertyMap::push).
+    pub fn multi(&self) -> impl Iterator<Item = &Property> {
+        self.entries.iter().flat_map(|(key, properties)| {
+            let skip = usize::from(!may_repeat(key));
+            properties.iter().skip(skip)
+        })
+    }
+
+    /// Takes out the `Property`s returned by [`multi`](PropertyMap::multi)
+    fn take_multi(&mut self) -> Vec<Property> {
+        let mut taken = Vec::new();
+        self.entries.retain(|key, properties| {
+            let keep = usize::from(!may_repeat(key)).min(properties.len());
+            taken.extend(properties.drain(keep..));
+            !properties.is_empty()
+        });
+        taken
+    }
+
+    /// Keeps only the `Property`s for which `predicate` returns `true`
+    pub fn retain<F: FnMut(&Property) -> bool>(&mut self, mut predicate: F) {
+        self.entries.retain(|_, properties| {
+            properties.retain(&mut predicate);
+            !properties.is_empty()
+        });
+    }
+
+    /// The first `Property` of every key together with the key, ordered by key
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Property)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, properties)| Some((key.as_str(), properties.first()?)))
+    }
+
+    /// The keys in order, each once
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// All `Property`s ordered by key
+    pub fn values(&self) -> impl Iterator<Item = &Property> {
+        self.entries.values().flatten()
+    }
+
+    /// Mutable access to all `Property`s ordered by key
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Property> {
+        self.entries.values_mut().flatten()
+    }
+
+    /// The number of `Property`s
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    /// Whether there are no `Property`s
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Index<&str> for PropertyMap {
+    type Output = Property;
+
+    /// The first `Property` with the given key
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such `Property`.
+    fn index(&self, key: &str) -> &Property {
+        self.get(key).expect("no property with this key")
+    }
+}
+
+impl<'a> IntoIterator for &'a PropertyMap {
+    type Item = &'a Property;
+    type IntoIter = Flatten<btree_map::Values<'a, String, Vec<Property>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.values().flatten()
+    }
+}
+
+impl Extend<Property> for PropertyMap {
+    /// Inserts every `Property` as [`insert`](PropertyMap::insert) does
+    fn extend<I: IntoIterator<Item = Property>>(&mut self, properties: I) {
+        for property in properties {
+            self.insert(property);
+        }
+    }
+}
+
+/// Mutable access to the `Property`s of which there may be many, as a `Vec`
+///
+/// Returned by [`Component::multi_properties_mut`](crate::Component::multi_properties_mut). The
+/// properties are taken out of the component and put back in when this is dropped.
+#[derive(Debug)]
+pub struct MultiPropertiesMut<'a> {
+    map: &'a mut PropertyMap,
+    properties: Vec<Property>,
+}
+
+impl<'a> MultiPropertiesMut<'a> {
+    pub(crate) fn new(map: &'a mut PropertyMap) -> Self {
+        let properties = map.take_multi();
+        MultiPropertiesMut { map, properties }
+    }
+}
+
+impl Deref for MultiPropertiesMut<'_> {
+    type Target = Vec<Property>;
+
+    fn deref(&self) -> &Vec<Property> {
+        &self.properties
+    }
+}
+
+impl DerefMut for MultiPropertiesMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<Property> {
+        &mut self.properties
+    }
+}
+
+impl Drop for MultiPropertiesMut<'_> {
+    fn drop(&mut self) {
+        for property in mem::take(&mut self.properties) {
+            self.map.push(property);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeating_properties() {
+        let mut map = PropertyMap::new();
+        map.insert(Property::new("SUMMARY", "first"));
+        let replaced = map.insert(Property::new("SUMMARY", "second"));
+        assert_eq!(replaced.unwrap().value(), "first");
+        map.insert(Property::new("ATTENDEE", "mailto:a@example.com"));
+        assert!(map
+            .insert(Property::new("ATTENDEE", "mailto:b@example.com"))
+            .is_none());
+        map.push(Property::new("DESCRIPTION", "one"));
+        map.push(Property::new("DESCRIPTION", "two"));
+
+        assert_eq!(map.len(), 5);
+        assert_eq!(map["SUMMARY"].value(), "second");
+        assert_eq!(map.get_all("ATTENDEE").len(), 2);
+        assert_eq!(map.get_all("LOCATION").len(), 0);
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            ["ATTENDEE", "DESCRIPTION", "SUMMARY"]
+        );
+        let values = map
+            .values()
+            .map(|property| property.value())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                "mailto:a@example.com",
+                "mailto:b@example.com",
+                "one",
+                "two",
+                "second"
+            ]
+        );
+
+        let multi = map
+            .multi()
+            .map(|property| property.value())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            multi,
+            ["mailto:a@example.com", "mailto:b@example.com", "two"]
+        );
+        let firsts = map
+            .iter()
+            .map(|(key, property)| (key, property.value()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            firsts,
+            [
+                ("ATTENDEE", "mailto:a@example.com".into()),
+                ("DESCRIPTION", "one".into()),
+                ("SUMMARY", "second".into())
+            ]
+        );
+        MultiPropertiesMut::new(&mut map).retain(|property| property.value() != "two");
+        assert_eq!(map.get_all("DESCRIPTION").len(), 1);
+        assert_eq!(map.len(), 4);
+        map.push(Property::new("DESCRIPTION", "two"));
+
+        map.retain(|property| property.value() != "mailto:a@example.com");
+        assert_eq!(map["ATTENDEE"].value(), "mailto:b@example.com");
+        assert_eq!(map.remove("DESCRIPTION").len(), 2);
+        map.retain(|property| property.key() != "ATTENDEE");
+        assert!(!map.contains_key("ATTENDEE"));
+        assert_eq!(map.len(), 1);
+    }
+}
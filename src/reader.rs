@@ -0,0 +1,386 @@
+//! Incremental reading of iCalendar text from an [`io::BufRead`]
+
+use std::io;
+
+use crate::calendar::CalendarElement;
+use crate::parser::{build_element, parse_content_line, ParseError, RawComponent};
+use crate::properties::Property;
+
+/// What a [`Reader`] comes across, one content line at a time
+#[derive(Clone, Debug)]
+pub enum ParseEvent {
+    /// A `BEGIN` line, with the kind of component in upper case, e.g. `VEVENT`
+    Begin(String),
+    /// A `Property` of the innermost component that has begun
+    Property(Property),
+    /// The `END` line of the innermost component that has begun
+    End(String),
+}
+
+/// A content line after unfolding, together with the line number it started on
+#[derive(Debug)]
+pub(crate) struct ContentLine {
+    pub(crate) number: usize,
+    pub(crate) text: String,
+}
+
+/// Pull-parser over iCalendar text, reading one content line at a time
+///
+/// Iterating a `Reader` yields [`ParseEvent`]s. Components are checked to end in the order they
+/// began, errors are returned once and end the iteration. Call [`components`](Reader::components)
+/// to get whole components instead.
+///
+/// Only the content line at hand is kept in memory, so files of any size can be read.
+///
+/// ```rust
+/// # use icalendar::*;
+/// let input = "BEGIN:VCALENDAR\r\n\
+///              BEGIN:VEVENT\r\n\
+///              SUMMARY:Planning\r\n\
+///              END:VEVENT\r\n\
+///              END:VCALENDAR\r\n";
+///
+/// let mut reader = Reader::new(input.as_bytes());
+/// assert!(matches!(reader.next(), Some(Ok(ParseEvent::Begin(kind))) if kind == "VCALENDAR"));
+/// assert!(matches!(reader.next(), Some(Ok(ParseEvent::Begin(kind))) if kind == "VEVENT"));
+/// assert_eq!(reader.depth(), 2);
+/// ```
+#[derive(Debug)]
+pub struct Reader<R> {
+    input: R,
+    line_number: usize,
+    peeked: Option<ContentLine>,
+    open: Vec<String>,
+    finished: bool,
+}
+
+impl<R: io::BufRead> Reader<R> {
+    /// Creates a new Reader, wrap unbuffered input such as a `File` in an [`io::BufReader`].
+    pub fn new(input: R) -> Self {
+        Reader {
+            input,
+            line_number: 0,
+            peeked: None,
+            open: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// How many components have begun and not yet ended
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Turns this into an iterator over the components, see [`Components`]
+    pub fn components(self) -> Components<R> {
+        Components {
+            reader: self,
+            open: Vec::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// Reads the next line as it is in the input, without its line break
+    fn physical_line(&mut self) -> Result<Option<ContentLine>, ParseError> {
+        let mut text = String::new();
+        let read = self
+            .input
+            .read_line(&mut text)
+            .map_err(|error| ParseError::at_line(self.line_number + 1, error.to_string()))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.line_number += 1;
+        if text.ends_with('\n') {
+            text.pop();
+            if text.ends_with('\r') {
+                text.pop();
+            }
+        }
+        Ok(Some(ContentLine {
+            number: self.line_number,
+            text,
+        }))
+    }
+
+    /// Reads the next content line, joining lines that were folded by inserting `CRLF` followed
+    /// by a single space or tab.
+    ///
+    /// Lines ending in a bare `LF` are accepted as well, since many exporters produce them.
+    pub(crate) fn content_line(&mut self) -> Result<Option<ContentLine>, ParseError> {
+        let mut line = loop {
+            match self.peeked.take() {
+                Some(line) if !line.text.is_empty() => break line,
+                Some(_) => {}
+                None => match self.physical_line()? {
+                    Some(line) if !line.text.is_empty() => break line,
+                    Some(_) => {}
+                    None => return Ok(None),
+                },
+            }
+        };
+        while let Some(next) = self.physical_line()? {
+            if next.text.starts_with([' ', '\t']) {
+                line.text.push_str(&next.text[1..]);
+            } else {
+                self.peeked = Some(next);
+                break;
+            }
+        }
+        Ok(Some(line))
+    }
+
+    fn read_event(&mut self) -> Option<Result<ParseEvent, ParseError>> {
+        let line = match self.content_line() {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                return self
+                    .open
+                    .pop()
+                    .map(|kind| Err(ParseError::new(format!("missing END:{}", kind))))
+            }
+            Err(error) => return Some(Err(error)),
+        };
+        let property = match parse_content_line(&line.text) {
+            Ok(property) => property,
+            Err(message) => return Some(Err(ParseError::at_line(line.number, message))),
+        };
+
+        Some(match property.key() {
+            "BEGIN" => {
+                let kind = property.value().to_uppercase();
+                self.open.push(kind.clone());
+                Ok(ParseEvent::Begin(kind))
+            }
+            "END" => match self.open.pop() {
+                None => Err(ParseError::at_line(
+                    line.number,
+                    format!("unexpected END:{}", property.value()),
+                )),
+                Some(kind) if !kind.eq_ignore_ascii_case(&property.value()) => {
+                    Err(ParseError::at_line(
+                        line.number,
+                        format!("expected END:{}, found END:{}", kind, property.value()),
+                    ))
+                }
+                Some(kind) => Ok(ParseEvent::End(kind)),
+            },
+            _ if self.open.is_empty() => Err(ParseError::at_line(
+                line.number,
+                format!("property {} outside of any component", property.key()),
+            )),
+            _ => Ok(ParseEvent::Property(property)),
+        })
+    }
+}
+
+impl<R: io::BufRead> Iterator for Reader<R> {
+    type Item = Result<ParseEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let event = self.read_event();
+        if !matches!(event, Some(Ok(_))) {
+            self.finished = true;
+        }
+        event
+    }
+}
+
+/// Iterator over the components of the calendars read by a [`Reader`]
+///
+/// Yields every `VEVENT`, `VTODO`, `VJOURNAL`, `VFREEBUSY`, `VVENUE` and `VTIMEZONE` as soon as
+/// it has ended, other components are skipped. Only the component at hand is kept in memory,
+/// next to the properties of the `VCALENDAR`.
+///
+/// ```rust
+/// # use icalendar::*;
+/// let input = "BEGIN:VCALENDAR\r\n\
+///              X-WR-CALNAME:Chores\r\n\
+///              BEGIN:VTODO\r\n\
+///              SUMMARY:Buy some milk\r\n\
+///              END:VTODO\r\n\
+///              BEGIN:VTODO\r\n\
+///              SUMMARY:Take out the trash\r\n\
+///              END:VTODO\r\n\
+///              END:VCALENDAR\r\n";
+///
+/// let mut components = Reader::new(input.as_bytes()).components();
+/// let mut count = 0;
+/// for component in &mut components {
+///     assert!(matches!(component, Ok(CalendarElement::Todo(_))));
+///     count += 1;
+/// }
+/// assert_eq!(count, 2);
+/// assert_eq!(components.properties()[0].value(), "Chores");
+/// ```
+#[derive(Debug)]
+pub struct Components<R> {
+    reader: Reader<R>,
+    open: Vec<RawComponent>,
+    properties: Vec<Property>,
+}
+
+impl<R> Components<R> {
+    /// The properties of the `VCALENDAR`s read so far
+    pub fn properties(&self) -> &[Property] {
+        &self.properties
+    }
+}
+
+impl<R: io::BufRead> Iterator for Components<R> {
+    type Item = Result<CalendarElement, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.reader.next()? {
+                Ok(event) => event,
+                Err(error) => return Some(Err(error)),
+            };
+            match event {
+                ParseEvent::Begin(kind) | ParseEvent::End(kind)
+                    if self.open.is_empty() && kind == "VCALENDAR" => {}
+                ParseEvent::Property(property) if self.open.is_empty() => {
+                    self.properties.push(property)
+                }
+                ParseEvent::Begin(kind) => self.open.push(RawComponent::new(kind)),
+                ParseEvent::Property(property) => {
+                    if let Some(component) = self.open.last_mut() {
+                        component.properties.push(property);
+                    }
+                }
+                ParseEvent::End(_) => {
+                    if let Some(component) = self.open.pop() {
+                        match self.open.last_mut() {
+                            Some(parent) => parent.components.push(component),
+                            None => {
+                                if let Some(element) = build_element(component) {
+                                    return Some(Ok(element));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rebuilds the tree of components described by `BEGIN` and `END` lines
+pub(crate) fn parse_components<R: io::BufRead>(input: R) -> Result<Vec<RawComponent>, ParseError> {
+    let mut finished = Vec::new();
+    let mut open: Vec<RawComponent> = Vec::new();
+
+    for event in Reader::new(input) {
+        match event? {
+            ParseEvent::Begin(kind) => open.push(RawComponent::new(kind)),
+            ParseEvent::Property(property) => {
+                if let Some(component) = open.last_mut() {
+                    component.properties.push(property);
+                }
+            }
+            ParseEvent::End(_) => {
+                if let Some(component) = open.pop() {
+                    match open.last_mut() {
+                        Some(parent) => parent.components.push(component),
+                        None => finished.push(component),
+                    }
+                }
+            }
+        }
+    }
+    Ok(finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Component;
+
+    fn content_lines(input: &str) -> Vec<ContentLine> {
+        let mut reader = Reader::new(input.as_bytes());
+        let mut lines = Vec::new();
+        while let Some(line) = reader.content_line().unwrap() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    #[test]
+    fn unfold_joins_continuation_lines() {
+        let lines = content_lines(
+            "DESCRIPTION:This is a lo\r\n ng description\r\n\tthat goes on\r\nUID:1\r\n",
+        );
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0].text,
+            "DESCRIPTION:This is a long descriptionthat goes on"
+        );
+        assert_eq!(lines[1].text, "UID:1");
+        assert_eq!(lines[1].number, 4);
+    }
+
+    #[test]
+    fn unfold_bare_line_feeds_and_blank_lines() {
+        let lines = content_lines("\nSUMMARY:a\n b\n\nUID:1");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "SUMMARY:ab");
+        assert_eq!(lines[0].number, 2);
+        assert_eq!(lines[1].text, "UID:1");
+    }
+
+    #[test]
+    fn events_and_errors() {
+        let events: Vec<_> =
+            Reader::new("BEGIN:VTODO\nSUMMARY:x\nEND:VEVENT\nEND:VTODO\n".as_bytes()).collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[1], Ok(ParseEvent::Property(p)) if p.value() == "x"));
+        let error = events[2].as_ref().unwrap_err();
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(error.message(), "expected END:VTODO, found END:VEVENT");
+
+        let mut reader = Reader::new("BEGIN:VTODO\n".as_bytes());
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().message(),
+            "missing END:VTODO"
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        // the line after is read ahead, to see whether it continues the one before
+        let mut reader = Reader::new(&b"BEGIN:VTODO\nSUMMARY:\xff\n"[..]);
+        assert_eq!(reader.next().unwrap().unwrap_err().line(), Some(2));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn components_skip_unknown_kinds() {
+        let input = "BEGIN:VCALENDAR\r\n\
+                     METHOD:PUBLISH\r\n\
+                     BEGIN:X-UNKNOWN\r\n\
+                     BEGIN:VEVENT\r\n\
+                     END:VEVENT\r\n\
+                     END:X-UNKNOWN\r\n\
+                     BEGIN:VEVENT\r\n\
+                     UID:1\r\n\
+                     BEGIN:VALARM\r\n\
+                     ACTION:DISPLAY\r\n\
+                     END:VALARM\r\n\
+                     END:VEVENT\r\n\
+                     END:VCALENDAR\r\n";
+        let mut components = Reader::new(input.as_bytes()).components();
+        match components.next() {
+            Some(Ok(CalendarElement::Event(event))) => {
+                assert_eq!(event.alarms().len(), 1);
+            }
+            other => panic!("expected an event, got {:?}", other),
+        }
+        assert!(components.next().is_none());
+        assert_eq!(components.properties().len(), 1);
+    }
+}
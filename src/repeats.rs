@@ -1,8 +1,13 @@
 //! Ways to repeat an event
 //!
-//! Taken from the rfc:
+//! A [`RecurrenceRule`] describes the `RRULE` property
+//! [(RFC 5545, Section 3.8.5.3)](https://tools.ietf.org/html/rfc5545#section-3.8.5.3),
+//! its parts are described in [RFC 5545, Section 3.3.10](https://tools.ietf.org/html/rfc5545#section-3.3.10).
 //!
-//! ```
+//! Depending on the `FREQ` of a rule the `BYxxx` parts either expand the set of occurrences or
+//! limit it. Taken from the rfc:
+//!
+//! ```text
 //!   +----------+--------+--------+-------+-------+------+-------+------+
 //!   |          |SECONDLY|MINUTELY|HOURLY |DAILY  |WEEKLY|MONTHLY|YEARLY|
 //!   +----------+--------+--------+-------+-------+------+-------+------+
@@ -25,82 +30,665 @@
 //!   |BYSETPOS  |Limit   |Limit   |Limit  |Limit  |Limit |Limit  |Limit |
 //!   +----------+--------+--------+-------+-------+------+-------+------+
 //! ```
+//!
+//! ```rust
+//! # use icalendar::*;
+//! # use chrono::Weekday;
+//! // every other week on tuesday and thursday, ten times
+//! let rule = RecurrenceRule::new(Freq::Weekly)
+//!     .interval(2)
+//!     .count(10)
+//!     .by_day(vec![Weekday::Tue, Weekday::Thu])
+//!     .done()
+//!     .unwrap();
+//! assert_eq!(rule.to_string(), "FREQ=WEEKLY;INTERVAL=2;COUNT=10;BYDAY=TU,TH");
+//! ```
+
+use chrono::{NaiveDate, Weekday};
 
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
 
-//     RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU
-//     RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU
-
-use chrono::{DateTime, Local};
-
-//#[derive(Debug,Clone)]
-//pub struct Every {
-//    freq: Freq,
-//    limit: Vec<Limit>,
-//    interval: usize,
-//    recurrence: Recurrence
-//}
-//
-//impl Every {
-//    pub fn new() -> Self {
-//        Every {
-//            freq: None,
-//            limit: Vec::new(),
-//            interval: 0,
-//            recurrence: None
-//        }
-//    }
-//    pub fn second(&mut self) -> &mut Self {
-//        self.interval = 2;
-//        self
-//    }
-//}
-
-#[derive(Debug,Clone)]
+use crate::components::CalendarDateTime;
+use crate::parser::ParseError;
+use crate::properties::Property;
+
+/// The `FREQ` rule part, identifies the type of recurrence rule
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Freq {
-    Daily,
-    Hourly,
+    /// Repeating events based on an interval of a second or more
+    Secondly,
+    /// Repeating events based on an interval of a minute or more
     Minutely,
-    Monthly,
+    /// Repeating events based on an interval of an hour or more
+    Hourly,
+    /// Repeating events based on an interval of a day or more
+    #[default]
+    Daily,
+    /// Repeating events based on an interval of a week or more
     Weekly,
+    /// Repeating events based on an interval of a month or more
+    Monthly,
+    /// Repeating events based on an interval of a year or more
     Yearly,
-    Secondly,
-    None
 }
 
-#[derive(Debug,Clone)]
+impl Freq {
+    fn as_str(self) -> &'static str {
+        match self {
+            Freq::Secondly => "SECONDLY",
+            Freq::Minutely => "MINUTELY",
+            Freq::Hourly => "HOURLY",
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+            Freq::Yearly => "YEARLY",
+        }
+    }
+}
+
+impl FromStr for Freq {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "SECONDLY" => Freq::Secondly,
+            "MINUTELY" => Freq::Minutely,
+            "HOURLY" => Freq::Hourly,
+            "DAILY" => Freq::Daily,
+            "WEEKLY" => Freq::Weekly,
+            "MONTHLY" => Freq::Monthly,
+            "YEARLY" => Freq::Yearly,
+            _ => return Err(ParseError::new(format!("unknown FREQ {:?}", s))),
+        })
+    }
+}
+
+/// Bounds a recurrence either by a number of occurrences (`COUNT`) or by a last date (`UNTIL`)
+///
+/// The value of `UNTIL` must have the same value type as `DTSTART`. If `DTSTART` references a
+/// time zone, `UNTIL` has to be given in UTC.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Limit {
-    Until(DateTime<Local>),
-    Count(usize),
-    Interval(usize)
+    /// `COUNT`: the number of occurrences, including `DTSTART`
+    Count(u32),
+    /// `UNTIL` for rules starting at a date-time
+    Until(CalendarDateTime),
+    /// `UNTIL` for rules starting at a date
+    UntilDate(NaiveDate),
 }
 
-#[derive(Debug,Clone)]
-pub enum Recurrence {
-    //Bysecond( Byseclist )
-    //Byminute( Byminlist )
-    //Byhour( Byhrlist )
-    //Byday( Bywdaylist )
-    //Bymonthday( Bymodaylist )
-    //Byyearday( Byyrdaylist )
-    //Byweekno( Bywknolist )
-    //Bymonth( Bymolist )
-    //Bysetpos( Bysplist )
-    //Wkst( Weekday ),
-    None
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Count(count) => write!(f, "COUNT={}", count),
+            Limit::Until(until) => write!(f, "UNTIL={}", until),
+            Limit::UntilDate(until) => write!(f, "UNTIL={}", until.format("%Y%m%d")),
+        }
+    }
 }
 
+/// An entry of the `BYDAY` rule part: a weekday, optionally with an ordinal such as `-1SU`
+///
+/// The ordinal is only meaningful for `MONTHLY` and `YEARLY` rules, where `-1SU` means the last
+/// sunday of the month or year respectively.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WeekdayNum {
+    /// `+n` / `-n` occurrence of the weekday within the month or year
+    pub ordinal: Option<i8>,
+    /// The day of the week
+    pub weekday: Weekday,
+}
+
+impl WeekdayNum {
+    /// Every `weekday`
+    pub fn every(weekday: Weekday) -> Self {
+        WeekdayNum {
+            ordinal: None,
+            weekday,
+        }
+    }
+
+    /// The n-th `weekday`, counting from the end if `n` is negative
+    pub fn nth(n: i8, weekday: Weekday) -> Self {
+        WeekdayNum {
+            ordinal: Some(n),
+            weekday,
+        }
+    }
+}
+
+impl From<Weekday> for WeekdayNum {
+    fn from(weekday: Weekday) -> Self {
+        WeekdayNum::every(weekday)
+    }
+}
+
+impl fmt::Display for WeekdayNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{}", ordinal)?;
+        }
+        f.write_str(weekday_to_str(self.weekday))
+    }
+}
+
+impl FromStr for WeekdayNum {
+    type Err = ParseError;
 
-enum Weekday {
-    Su, Mo, Tu, We, Th, Fr, Sa
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() < 2 || !s.is_char_boundary(s.len() - 2) {
+            return Err(ParseError::new(format!("invalid BYDAY value {:?}", s)));
+        }
+        let (ordinal, weekday) = s.split_at(s.len() - 2);
+        let ordinal = if ordinal.is_empty() {
+            None
+        } else {
+            Some(parse_number(ordinal, "BYDAY", -53, 53)?)
+        };
+        Ok(WeekdayNum {
+            ordinal,
+            weekday: parse_weekday(weekday)?,
+        })
+    }
 }
 
+fn weekday_to_str(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
 
+fn parse_weekday(s: &str) -> Result<Weekday, ParseError> {
+    Ok(match s {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(ParseError::new(format!("invalid weekday {:?}", s))),
+    })
+}
 
+/// Checks that `number` is within `min..=max`, zero is only accepted if `min` is zero.
+fn check_number<T>(number: T, part: &str, min: T, max: T) -> Result<T, ParseError>
+where
+    T: PartialOrd + Default + Copy + fmt::Display,
+{
+    if number < min || number > max || (min < T::default() && number == T::default()) {
+        return Err(ParseError::new(format!(
+            "{} value {} is out of range",
+            part, number
+        )));
+    }
+    Ok(number)
+}
 
+/// Parses a number within `min..=max` as [`check_number`] accepts it.
+fn parse_number<T>(s: &str, part: &str, min: T, max: T) -> Result<T, ParseError>
+where
+    T: FromStr + PartialOrd + Default + Copy + fmt::Display,
+{
+    let number = s
+        .trim_start_matches('+')
+        .parse::<T>()
+        .map_err(|_| ParseError::new(format!("invalid {} value {:?}", part, s)))?;
+    check_number(number, part, min, max)
+}
 
+fn parse_list<T>(s: &str, part: &str, min: T, max: T) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr + PartialOrd + Default + Copy + fmt::Display,
+{
+    s.split(',')
+        .map(|value| parse_number(value, part, min, max))
+        .collect()
+}
 
+fn check_list<T>(list: &[T], part: &str, min: T, max: T) -> Result<(), ParseError>
+where
+    T: PartialOrd + Default + Copy + fmt::Display,
+{
+    for number in list {
+        check_number(*number, part, min, max)?;
+    }
+    Ok(())
+}
 
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, part: &str, list: &[T]) -> fmt::Result {
+    if list.is_empty() {
+        return Ok(());
+    }
+    write!(f, ";{}=", part)?;
+    for (i, value) in list.iter().enumerate() {
+        if i > 0 {
+            f.write_str(",")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    Ok(())
+}
 
+/// A typed `RRULE` value [(RFC 5545, Section 3.3.10)](https://tools.ietf.org/html/rfc5545#section-3.3.10)
+///
+/// Build one with [`RecurrenceRule::new`] and attach it to a component with
+/// [`Component::repeats`](crate::Component::repeats).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurrenceRule {
+    pub(crate) freq: Freq,
+    pub(crate) interval: u32,
+    pub(crate) limit: Option<Limit>,
+    pub(crate) by_second: Vec<u8>,
+    pub(crate) by_minute: Vec<u8>,
+    pub(crate) by_hour: Vec<u8>,
+    pub(crate) by_day: Vec<WeekdayNum>,
+    pub(crate) by_month_day: Vec<i8>,
+    pub(crate) by_year_day: Vec<i16>,
+    pub(crate) by_week_no: Vec<i8>,
+    pub(crate) by_month: Vec<u8>,
+    pub(crate) by_set_pos: Vec<i16>,
+    pub(crate) week_start: Option<Weekday>,
+}
 
+impl Default for RecurrenceRule {
+    fn default() -> Self {
+        RecurrenceRule {
+            freq: Freq::default(),
+            interval: 1,
+            limit: None,
+            by_second: Vec::new(),
+            by_minute: Vec::new(),
+            by_hour: Vec::new(),
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_year_day: Vec::new(),
+            by_week_no: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: None,
+        }
+    }
+}
 
-pub trait Repeater: Sized {}
+impl RecurrenceRule {
+    /// Creates a new rule repeating with the given frequency, forever.
+    pub fn new(freq: Freq) -> Self {
+        RecurrenceRule {
+            freq,
+            ..Default::default()
+        }
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    ///
+    /// Fails if the rule is not valid, see [`validate`](RecurrenceRule::validate).
+    pub fn done(&mut self) -> Result<Self, ParseError> {
+        self.validate()?;
+        Ok(mem::take(self))
+    }
+
+    /// Checks every rule part against the range RFC 5545 allows for it
+    ///
+    /// These are the ranges that parsing a rule enforces as well, so a valid rule can be read
+    /// back after writing it. Ordinals in `BYDAY` are only allowed on `MONTHLY` and `YEARLY`
+    /// rules and `BYWEEKNO` only on `YEARLY` rules.
+    pub fn validate(&self) -> Result<(), ParseError> {
+        check_number(self.interval, "INTERVAL", 1, u32::MAX)?;
+        if let Some(Limit::Count(count)) = self.limit {
+            check_number(count, "COUNT", 1, u32::MAX)?;
+        }
+        check_list(&self.by_second, "BYSECOND", 0, 60)?;
+        check_list(&self.by_minute, "BYMINUTE", 0, 59)?;
+        check_list(&self.by_hour, "BYHOUR", 0, 23)?;
+        for day in &self.by_day {
+            if let Some(ordinal) = day.ordinal {
+                if !matches!(self.freq, Freq::Monthly | Freq::Yearly) {
+                    return Err(ParseError::new(format!(
+                        "BYDAY ordinals are not allowed on {} rules",
+                        self.freq.as_str()
+                    )));
+                }
+                check_number(ordinal, "BYDAY", -53, 53)?;
+            }
+        }
+        check_list(&self.by_month_day, "BYMONTHDAY", -31, 31)?;
+        check_list(&self.by_year_day, "BYYEARDAY", -366, 366)?;
+        if !self.by_week_no.is_empty() && self.freq != Freq::Yearly {
+            return Err(ParseError::new(format!(
+                "BYWEEKNO is not allowed on {} rules",
+                self.freq.as_str()
+            )));
+        }
+        check_list(&self.by_week_no, "BYWEEKNO", -53, 53)?;
+        check_list(&self.by_month, "BYMONTH", 1, 12)?;
+        check_list(&self.by_set_pos, "BYSETPOS", -366, 366)?;
+        Ok(())
+    }
+
+    /// The `FREQ` of this rule
+    pub fn freq(&self) -> Freq {
+        self.freq
+    }
+
+    /// Set the `INTERVAL`
+    ///
+    /// `2` on a `WEEKLY` rule means every other week, zero is invalid.
+    pub fn interval(&mut self, interval: u32) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the `COUNT`, replacing a previously set `UNTIL`, zero is invalid
+    pub fn count(&mut self, count: u32) -> &mut Self {
+        self.limit = Some(Limit::Count(count));
+        self
+    }
+
+    /// Set the `UNTIL` date-time, replacing a previously set `COUNT`
+    ///
+    /// See [CalendarDateTime] for info how are different [chrono] types converted automatically.
+    pub fn until<T: Into<CalendarDateTime>>(&mut self, dt: T) -> &mut Self {
+        self.limit = Some(Limit::Until(dt.into()));
+        self
+    }
+
+    /// Set the `UNTIL` date, replacing a previously set `COUNT`
+    ///
+    /// Use this for rules of all day events.
+    pub fn until_date(&mut self, date: NaiveDate) -> &mut Self {
+        self.limit = Some(Limit::UntilDate(date));
+        self
+    }
+
+    /// Set the `BYSECOND` list, valid values are 0 to 60
+    pub fn by_second<I: IntoIterator<Item = u8>>(&mut self, seconds: I) -> &mut Self {
+        self.by_second = seconds.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYMINUTE` list, valid values are 0 to 59
+    pub fn by_minute<I: IntoIterator<Item = u8>>(&mut self, minutes: I) -> &mut Self {
+        self.by_minute = minutes.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYHOUR` list, valid values are 0 to 23
+    pub fn by_hour<I: IntoIterator<Item = u8>>(&mut self, hours: I) -> &mut Self {
+        self.by_hour = hours.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYDAY` list
+    ///
+    /// Accepts plain [`Weekday`]s as well as [`WeekdayNum`]s carrying an ordinal.
+    pub fn by_day<D, I>(&mut self, days: I) -> &mut Self
+    where
+        D: Into<WeekdayNum>,
+        I: IntoIterator<Item = D>,
+    {
+        self.by_day = days.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the `BYMONTHDAY` list, valid values are 1 to 31 and -31 to -1
+    pub fn by_month_day<I: IntoIterator<Item = i8>>(&mut self, days: I) -> &mut Self {
+        self.by_month_day = days.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYYEARDAY` list, valid values are 1 to 366 and -366 to -1
+    pub fn by_year_day<I: IntoIterator<Item = i16>>(&mut self, days: I) -> &mut Self {
+        self.by_year_day = days.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYWEEKNO` list, valid values are 1 to 53 and -53 to -1
+    ///
+    /// Only valid for `YEARLY` rules.
+    pub fn by_week_no<I: IntoIterator<Item = i8>>(&mut self, weeks: I) -> &mut Self {
+        self.by_week_no = weeks.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYMONTH` list, valid values are 1 to 12
+    pub fn by_month<I: IntoIterator<Item = u8>>(&mut self, months: I) -> &mut Self {
+        self.by_month = months.into_iter().collect();
+        self
+    }
+
+    /// Set the `BYSETPOS` list, valid values are 1 to 366 and -366 to -1
+    ///
+    /// Selects occurrences from the set of occurrences within each interval, `-1` being the last
+    /// one.
+    pub fn by_set_pos<I: IntoIterator<Item = i16>>(&mut self, positions: I) -> &mut Self {
+        self.by_set_pos = positions.into_iter().collect();
+        self
+    }
+
+    /// Set the `WKST`, the day on which the work week starts
+    ///
+    /// Defaults to monday if not set.
+    pub fn week_start(&mut self, weekday: Weekday) -> &mut Self {
+        self.week_start = Some(weekday);
+        self
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    /// Formats the rule as the value of an `RRULE` property, starting with `FREQ`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.freq.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(ref limit) = self.limit {
+            write!(f, ";{}", limit)?;
+        }
+        write_list(f, "BYSECOND", &self.by_second)?;
+        write_list(f, "BYMINUTE", &self.by_minute)?;
+        write_list(f, "BYHOUR", &self.by_hour)?;
+        write_list(f, "BYDAY", &self.by_day)?;
+        write_list(f, "BYMONTHDAY", &self.by_month_day)?;
+        write_list(f, "BYYEARDAY", &self.by_year_day)?;
+        write_list(f, "BYWEEKNO", &self.by_week_no)?;
+        write_list(f, "BYMONTH", &self.by_month)?;
+        write_list(f, "BYSETPOS", &self.by_set_pos)?;
+        if let Some(week_start) = self.week_start {
+            write!(f, ";WKST={}", weekday_to_str(week_start))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = ParseError;
+
+    /// Parses the value of an `RRULE` property.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut freq = None;
+        let mut rule = RecurrenceRule::new(Freq::default());
+
+        for part in s.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = match part.find('=') {
+                Some(index) => (&part[..index], &part[index + 1..]),
+                None => return Err(ParseError::new(format!("invalid rule part {:?}", part))),
+            };
+            match name.to_uppercase().as_str() {
+                "FREQ" => freq = Some(value.to_uppercase().parse()?),
+                "INTERVAL" => {
+                    rule.interval(parse_number(value, "INTERVAL", 1, u32::MAX)?);
+                }
+                "COUNT" => {
+                    rule.count(parse_number(value, "COUNT", 1, u32::MAX)?);
+                }
+                "UNTIL" => {
+                    rule.limit = Some(if value.len() == 8 {
+                        NaiveDate::parse_from_str(value, "%Y%m%d")
+                            .map(Limit::UntilDate)
+                            .map_err(|_| ParseError::new(format!("invalid UNTIL {:?}", value)))?
+                    } else {
+                        CalendarDateTime::parse(value)
+                            .map(Limit::Until)
+                            .ok_or_else(|| ParseError::new(format!("invalid UNTIL {:?}", value)))?
+                    });
+                }
+                "BYSECOND" => rule.by_second = parse_list(value, "BYSECOND", 0, 60)?,
+                "BYMINUTE" => rule.by_minute = parse_list(value, "BYMINUTE", 0, 59)?,
+                "BYHOUR" => rule.by_hour = parse_list(value, "BYHOUR", 0, 23)?,
+                "BYDAY" => {
+                    rule.by_day = value
+                        .to_uppercase()
+                        .split(',')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => rule.by_month_day = parse_list(value, "BYMONTHDAY", -31, 31)?,
+                "BYYEARDAY" => rule.by_year_day = parse_list(value, "BYYEARDAY", -366, 366)?,
+                "BYWEEKNO" => rule.by_week_no = parse_list(value, "BYWEEKNO", -53, 53)?,
+                "BYMONTH" => rule.by_month = parse_list(value, "BYMONTH", 1, 12)?,
+                "BYSETPOS" => rule.by_set_pos = parse_list(value, "BYSETPOS", -366, 366)?,
+                "WKST" => rule.week_start = Some(parse_weekday(&value.to_uppercase())?),
+                _ => return Err(ParseError::new(format!("unknown rule part {:?}", name))),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| ParseError::new("FREQ is required"))?;
+        rule.validate()?;
+        Ok(rule)
+    }
+}
+
+impl From<RecurrenceRule> for Property {
+    fn from(rule: RecurrenceRule) -> Self {
+        Property::with_value("RRULE", rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn display_orders_parts() {
+        let rule = RecurrenceRule::new(Freq::Yearly)
+            .week_start(Weekday::Sun)
+            .by_set_pos(vec![-1])
+            .by_month(vec![3, 11])
+            .by_day(vec![
+                WeekdayNum::nth(2, Weekday::Sun),
+                WeekdayNum::nth(1, Weekday::Sun),
+            ])
+            .until(Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap())
+            .done()
+            .unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=YEARLY;UNTIL=20300101T000000Z;BYDAY=2SU,1SU;BYMONTH=3,11;BYSETPOS=-1;WKST=SU"
+        );
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        for rule in &[
+            "FREQ=DAILY;COUNT=10",
+            "FREQ=WEEKLY;INTERVAL=2;UNTIL=19971224;BYDAY=MO,WE,FR;WKST=SU",
+            "FREQ=MONTHLY;BYDAY=-2MO",
+            "FREQ=YEARLY;BYYEARDAY=1,100,200",
+            "FREQ=YEARLY;BYDAY=MO;BYWEEKNO=20",
+            "FREQ=MINUTELY;INTERVAL=20;BYHOUR=9,10,11,12,13,14,15,16",
+            "FREQ=MONTHLY;COUNT=3;BYDAY=TU,WE,TH;BYSETPOS=3",
+            "FREQ=HOURLY;INTERVAL=3;UNTIL=19970902T170000Z;BYSECOND=0,30;BYMINUTE=15",
+            "FREQ=MONTHLY;BYMONTHDAY=-3",
+        ] {
+            let parsed: RecurrenceRule = rule.parse().unwrap();
+            assert_eq!(&parsed.to_string(), rule);
+        }
+    }
+
+    #[test]
+    fn parse_is_case_insensitive_and_order_independent() {
+        let parsed: RecurrenceRule = "byday=+1su;freq=yearly;bymonth=4".parse().unwrap();
+        assert_eq!(parsed.to_string(), "FREQ=YEARLY;BYDAY=1SU;BYMONTH=4");
+    }
+
+    #[test]
+    fn validate_ranges() {
+        assert!(RecurrenceRule::new(Freq::Yearly)
+            .by_month(vec![12])
+            .validate()
+            .is_ok());
+        assert!(RecurrenceRule::new(Freq::Yearly)
+            .by_month(vec![13])
+            .validate()
+            .is_err());
+        assert!(RecurrenceRule::new(Freq::Daily)
+            .interval(0)
+            .validate()
+            .is_err());
+        assert!(RecurrenceRule::new(Freq::Monthly)
+            .by_month_day(vec![0])
+            .validate()
+            .is_err());
+        assert!(RecurrenceRule::new(Freq::Monthly)
+            .by_day(vec![WeekdayNum::nth(54, Weekday::Mon)])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn validate_parts_against_freq() {
+        assert!(RecurrenceRule::new(Freq::Monthly)
+            .by_day(vec![WeekdayNum::nth(-1, Weekday::Fri)])
+            .validate()
+            .is_ok());
+        assert!(RecurrenceRule::new(Freq::Weekly)
+            .by_day(vec![WeekdayNum::nth(-1, Weekday::Fri)])
+            .validate()
+            .is_err());
+        assert!(RecurrenceRule::new(Freq::Weekly)
+            .by_day(vec![Weekday::Fri])
+            .validate()
+            .is_ok());
+        assert!(RecurrenceRule::new(Freq::Yearly)
+            .by_week_no(vec![20])
+            .validate()
+            .is_ok());
+        assert!(RecurrenceRule::new(Freq::Monthly)
+            .by_week_no(vec![20])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn done_returns_validation_errors() {
+        let error = RecurrenceRule::new(Freq::Yearly)
+            .by_month(vec![13])
+            .done()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "BYMONTH value 13 is out of range");
+        assert_eq!(RecurrenceRule::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("COUNT=10".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=FORTNIGHTLY".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYMONTH=13".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYMONTHDAY=0".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYDAY=XX".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;FOO=1".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYDAY=1MO".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=MONTHLY;BYWEEKNO=1".parse::<RecurrenceRule>().is_err());
+    }
+}
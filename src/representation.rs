@@ -0,0 +1,254 @@
+//! Pieces shared by the jCal and xCal representations of calendars
+//!
+//! Both write dates, times and UTC offsets in the extended format of ISO 8601, such as
+//! `2021-03-01T08:00:00Z`, and give every item of a list value on its own.
+
+use crate::properties::{default_value_type, is_list_property, Property, ValueType};
+
+/// Splits the value of `property` into its items as written by iCalendar
+///
+/// TEXT lists such as CATEGORIES are unescaped, values that are never lists are kept whole.
+pub(crate) fn items(property: &Property, value_type: ValueType) -> Vec<String> {
+    let value = property.value();
+    match value_type {
+        ValueType::Text if is_list_property(property.key()) => split_text_list(&value),
+        ValueType::Date
+        | ValueType::DateTime
+        | ValueType::Duration
+        | ValueType::Period
+        | ValueType::Time => value.split(',').map(str::to_owned).collect(),
+        _ => vec![value.into_owned()],
+    }
+}
+
+/// Builds a property from the items of its value, adding a VALUE parameter unless
+/// `value_type` is the default of `key`
+pub(crate) fn build_property(key: &str, value_type: ValueType, items: Vec<String>) -> Property {
+    let value = if value_type == ValueType::Text && is_list_property(key) {
+        items
+            .iter()
+            .map(|item| item.replace('\\', "\\\\").replace(',', "\\,"))
+            .collect::<Vec<_>>()
+            .join(",")
+    } else {
+        items.join(",")
+    };
+    let mut property = Property::new(key, &value);
+    if value_type != default_value_type(key) {
+        property.append_parameter(value_type);
+    }
+    property
+}
+
+/// Converts a single value to the extended format, other values are kept as they are
+///
+/// `20210301T080000Z` becomes `2021-03-01T08:00:00Z`, `+0100` becomes `+01:00`.
+pub(crate) fn to_extended(value_type: ValueType, value: &str) -> String {
+    match value_type {
+        ValueType::Date | ValueType::DateTime => extended_date_time(value),
+        ValueType::Time => extended_time(value),
+        ValueType::UtcOffset => extended_utc_offset(value),
+        ValueType::Period => match value.split_once('/') {
+            Some((start, end)) if end.starts_with('P') => {
+                format!("{}/{}", extended_date_time(start), end)
+            }
+            Some((start, end)) => {
+                format!("{}/{}", extended_date_time(start), extended_date_time(end))
+            }
+            None => value.to_owned(),
+        },
+        ValueType::Boolean => value.to_lowercase(),
+        _ => value.to_owned(),
+    }
+}
+
+/// Reverses [`to_extended`]
+pub(crate) fn from_extended(value_type: ValueType, value: &str) -> String {
+    match value_type {
+        ValueType::Date
+        | ValueType::DateTime
+        | ValueType::Period
+        | ValueType::Time
+        | ValueType::UtcOffset => value
+            .char_indices()
+            // a leading `-` is the sign of an offset
+            .filter(|&(i, c)| c != ':' && (c != '-' || i == 0))
+            .map(|(_, c)| c)
+            .collect(),
+        ValueType::Boolean => value.to_uppercase(),
+        _ => value.to_owned(),
+    }
+}
+
+/// Splits a RECUR value into its parts and their items, UNTIL in extended format
+pub(crate) fn recur_parts(value: &str) -> Vec<(String, Vec<String>)> {
+    value
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(name, items)| {
+            let name = name.to_uppercase();
+            let items = items
+                .split(',')
+                .map(|item| match name.as_str() {
+                    "UNTIL" => extended_date_time(item),
+                    _ => item.to_owned(),
+                })
+                .collect();
+            (name, items)
+        })
+        .collect()
+}
+
+/// Reverses [`recur_parts`], putting FREQ first for compatibility with RFC 2445
+pub(crate) fn join_recur(mut parts: Vec<(String, Vec<String>)>) -> String {
+    parts.sort_by_key(|(name, _)| !name.eq_ignore_ascii_case("FREQ"));
+    parts
+        .into_iter()
+        .map(|(name, items)| {
+            let name = name.to_uppercase();
+            let items = items
+                .iter()
+                .map(|item| match name.as_str() {
+                    "UNTIL" => from_extended(ValueType::DateTime, item),
+                    _ => item.clone(),
+                })
+                .collect::<Vec<_>>();
+            format!("{}={}", name, items.join(","))
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn all_digits(value: &str) -> bool {
+    value.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// `20210301` becomes `2021-03-01` and `20210301T080000Z` becomes `2021-03-01T08:00:00Z`
+fn extended_date_time(value: &str) -> String {
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+    if date.len() != 8 || !all_digits(date) {
+        return value.to_owned();
+    }
+    let mut extended = format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]);
+    if let Some(time) = time {
+        extended.push('T');
+        extended.push_str(&extended_time(time));
+    }
+    extended
+}
+
+/// `080000Z` becomes `08:00:00Z`
+fn extended_time(value: &str) -> String {
+    match value.get(..6) {
+        Some(time) if all_digits(time) => format!(
+            "{}:{}:{}{}",
+            &time[..2],
+            &time[2..4],
+            &time[4..],
+            &value[6..]
+        ),
+        _ => value.to_owned(),
+    }
+}
+
+/// `+0100` becomes `+01:00` and `-013045` becomes `-01:30:45`
+fn extended_utc_offset(value: &str) -> String {
+    let digits = match value.strip_prefix(['+', '-']) {
+        Some(digits) if (digits.len() == 4 || digits.len() == 6) && all_digits(digits) => digits,
+        _ => return value.to_owned(),
+    };
+    let mut extended = format!("{}{}:{}", &value[..1], &digits[..2], &digits[2..4]);
+    if digits.len() == 6 {
+        extended.push(':');
+        extended.push_str(&digits[4..]);
+    }
+    extended
+}
+
+/// Splits the value of a list property such as CATEGORIES into its items
+fn split_text_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let item = items.last_mut().expect("there is always an item");
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ (',' | '\\')) => item.push(escaped),
+                Some(other) => {
+                    item.push('\\');
+                    item.push(other);
+                }
+                None => item.push('\\'),
+            },
+            ',' => items.push(String::new()),
+            c => item.push(c),
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_format_round_trip() {
+        for (value_type, basic, extended) in &[
+            (ValueType::Date, "20210301", "2021-03-01"),
+            (
+                ValueType::DateTime,
+                "20210301T080000Z",
+                "2021-03-01T08:00:00Z",
+            ),
+            (
+                ValueType::DateTime,
+                "20210301T080000",
+                "2021-03-01T08:00:00",
+            ),
+            (ValueType::Time, "080000", "08:00:00"),
+            (ValueType::UtcOffset, "-0500", "-05:00"),
+            (ValueType::UtcOffset, "+013045", "+01:30:45"),
+            (
+                ValueType::Period,
+                "20210301T080000Z/PT1H",
+                "2021-03-01T08:00:00Z/PT1H",
+            ),
+            (ValueType::Boolean, "TRUE", "true"),
+            (ValueType::Duration, "-PT15M", "-PT15M"),
+            (ValueType::Text, "2021-03-01", "2021-03-01"),
+        ] {
+            assert_eq!(to_extended(*value_type, basic), *extended);
+            assert_eq!(from_extended(*value_type, extended), *basic);
+        }
+        assert_eq!(to_extended(ValueType::DateTime, "soon"), "soon");
+    }
+
+    #[test]
+    fn list_items() {
+        let categories = Property::new("CATEGORIES", "Work,Fun\\, mostly");
+        let items = items(&categories, ValueType::Text);
+        assert_eq!(items, ["Work", "Fun, mostly"]);
+        assert_eq!(
+            build_property("CATEGORIES", ValueType::Text, items).value(),
+            categories.value()
+        );
+        let summary = Property::new("SUMMARY", "a, b");
+        assert_eq!(self::items(&summary, ValueType::Text), ["a, b"]);
+    }
+
+    #[test]
+    fn recur() {
+        let parts = recur_parts("FREQ=WEEKLY;BYDAY=MO,TU;UNTIL=20211231");
+        assert_eq!(parts[1], ("BYDAY".into(), vec!["MO".into(), "TU".into()]));
+        assert_eq!(parts[2].1, ["2021-12-31"]);
+        let mut reordered = parts.clone();
+        reordered.rotate_left(1);
+        assert_eq!(
+            join_recur(reordered),
+            "FREQ=WEEKLY;BYDAY=MO,TU;UNTIL=20211231"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! The shape components take when they are serialized with `serde`
+//!
+//! It does not follow how components store their properties, so serialized components stay
+//! readable when that changes: a flat list of properties in the order they are written, followed
+//! by the sub-components, such as `{"properties": [...], "alarms": [...]}`. Deserializing appends
+//! every property with [`Component::append_property`].
+
+use std::mem;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::alarm::Alarm;
+use crate::components::{Component, Event, FreeBusy, Journal, Todo, Venue};
+use crate::properties::Property;
+use crate::timezone::{Observance, ObservanceKind, Timezone};
+
+/// A component as it is serialized
+#[derive(Default, Serialize)]
+struct ShapeRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<ObservanceKind>,
+    properties: Vec<&'a Property>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    alarms: &'a [Alarm],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    observances: &'a [Observance],
+}
+
+impl<'a> ShapeRef<'a> {
+    fn new<C: Component>(component: &'a C) -> Self {
+        ShapeRef {
+            properties: component.properties().values().collect(),
+            alarms: component.alarms(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A component as it is deserialized, sub-components it can not hold are ignored
+#[derive(Deserialize)]
+struct Shape {
+    kind: Option<ObservanceKind>,
+    #[serde(default)]
+    properties: Vec<Property>,
+    #[serde(default)]
+    alarms: Vec<Alarm>,
+    #[serde(default)]
+    observances: Vec<Observance>,
+}
+
+impl Shape {
+    /// A component with all properties of this shape
+    fn build<C: Component + Default>(self) -> C {
+        let mut component = C::default();
+        for property in self.properties {
+            component.append_property(property);
+        }
+        component
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` for components without sub-components
+macro_rules! plain_shape {
+    ($($t:ty),+) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ShapeRef::new(self).serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    Ok(Shape::deserialize(deserializer)?.build())
+                }
+            }
+        )+
+    };
+}
+
+/// Implements `Serialize` and `Deserialize` for components holding alarms
+macro_rules! alarm_shape {
+    ($($t:ty),+) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ShapeRef::new(self).serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let mut shape = Shape::deserialize(deserializer)?;
+                    let alarms = mem::take(&mut shape.alarms);
+                    let mut component: $t = shape.build();
+                    for alarm in alarms {
+                        component.alarm(alarm);
+                    }
+                    Ok(component)
+                }
+            }
+        )+
+    };
+}
+
+plain_shape!(Journal, FreeBusy, Venue, Alarm);
+alarm_shape!(Event, Todo);
+
+impl Serialize for Timezone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ShapeRef {
+            observances: self.observances(),
+            ..ShapeRef::new(self)
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timezone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut shape = Shape::deserialize(deserializer)?;
+        let observances = mem::take(&mut shape.observances);
+        let mut timezone: Timezone = shape.build();
+        for observance in observances {
+            timezone.append_observance(observance.kind(), observance);
+        }
+        Ok(timezone)
+    }
+}
+
+impl Serialize for Observance {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ShapeRef {
+            kind: Some(self.kind()),
+            properties: self.properties().values().collect(),
+            ..Default::default()
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Observance {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let shape = Shape::deserialize(deserializer)?;
+        let mut observance = Observance::new();
+        observance.kind = shape.kind.unwrap_or_default();
+        for property in shape.properties {
+            observance.append_property(property);
+        }
+        Ok(observance)
+    }
+}
@@ -0,0 +1,419 @@
+use chrono::{Duration, FixedOffset, NaiveDateTime};
+
+use std::fmt;
+use std::mem;
+
+use crate::components::{
+    component_impl, CalendarDateTime, Component, DatePerhapsTime, InnerComponent,
+};
+use crate::occurrences::Occurrences;
+use crate::output::WriteOptions;
+#[cfg(any(feature = "jcal", feature = "xcal"))]
+use crate::parser::RawComponent;
+use crate::properties::Property;
+use crate::property_map::PropertyMap;
+use crate::repeats::RecurrenceRule;
+use crate::value::Value;
+
+/// VTIMEZONE [(RFC 5545, Section 3.6.5 )](https://tools.ietf.org/html/rfc5545#section-3.6.5)
+///
+/// Defines the observances of a time zone, so that
+/// [`CalendarDateTime::WithTimezone`](crate::CalendarDateTime::WithTimezone) values referencing
+/// its `TZID` can be resolved by calendar clients.
+///
+/// Named `Timezone` rather than `TimeZone`, so it does not clash with [`chrono::TimeZone`] when
+/// both crates are glob imported.
+///
+/// ```rust
+/// # use chrono::{FixedOffset, NaiveDate};
+/// # use icalendar::*;
+/// let start = NaiveDate::from_ymd_opt(1970, 3, 29).unwrap().and_hms_opt(2, 0, 0).unwrap();
+/// let cet = FixedOffset::east_opt(3600).unwrap();
+/// let cest = FixedOffset::east_opt(2 * 3600).unwrap();
+///
+/// let berlin = Timezone::new("Europe/Berlin")
+///     .daylight(
+///         Observance::new()
+///             .starts(start)
+///             .offset_from(cet)
+///             .offset_to(cest)
+///             .name("CEST")
+///             .repeats("FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU".parse().unwrap())
+///             .done(),
+///     )
+///     .done();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Timezone {
+    inner: InnerComponent,
+    observances: Vec<Observance>,
+}
+
+/// Kind of an [`Observance`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING-KEBAB-CASE"))]
+pub enum ObservanceKind {
+    /// `STANDARD`, e.g. winter time
+    #[default]
+    Standard,
+    /// `DAYLIGHT`, daylight saving time
+    Daylight,
+}
+
+impl ObservanceKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ObservanceKind::Standard => "STANDARD",
+            ObservanceKind::Daylight => "DAYLIGHT",
+        }
+    }
+}
+
+/// STANDARD or DAYLIGHT sub-component of a [`Timezone`]
+///
+/// Describes from when on which offset from UTC is observed, either once or, with
+/// [`repeats`](Observance::repeats), every year.
+#[derive(Clone, Debug, Default)]
+pub struct Observance {
+    pub(crate) kind: ObservanceKind,
+    inner: InnerComponent,
+}
+
+/// Formats an offset from UTC as `UTC-OFFSET` value, e.g. `+0100` or `-023030`
+pub(crate) fn format_utc_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if seconds == 0 {
+        format!("{}{:02}{:02}", sign, hours, minutes)
+    } else {
+        format!("{}{:02}{:02}{:02}", sign, hours, minutes, seconds)
+    }
+}
+
+/// Reads a `UTC-OFFSET` value, the reverse of [`format_utc_offset`]
+pub(crate) fn parse_utc_offset(s: &str) -> Option<FixedOffset> {
+    let sign = match s.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = s.get(1..)?;
+    if !(digits.len() == 4 || digits.len() == 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let number = |range| digits.get(range).unwrap_or("0").parse::<i32>().ok();
+    let seconds = number(0..2)? * 3600 + number(2..4)? * 60 + number(4..6)?;
+    FixedOffset::east_opt(sign * seconds)
+}
+
+impl Timezone {
+    /// Creates a new Timezone with the given TZID.
+    pub fn new(tzid: &str) -> Self {
+        let mut timezone = Timezone::default();
+        timezone.append_property(Property::new("TZID", tzid));
+        timezone
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        Timezone {
+            inner: self.inner.done(),
+            observances: mem::take(&mut self.observances),
+        }
+    }
+
+    /// Returns the TZID, which is referenced by `TZID` parameters of date-times.
+    pub fn tzid(&self) -> Option<&str> {
+        self.inner.properties.get("TZID")?.as_str()
+    }
+
+    /// Read-only access to the observances, in the order they were added
+    pub fn observances(&self) -> &[Observance] {
+        &self.observances
+    }
+
+    /// The offset from UTC that is observed at the wall clock time `local`
+    ///
+    /// This is the TZOFFSETTO of the observance that started last before `local`, or the
+    /// TZOFFSETFROM of the first observance if `local` lies before all of them. Returns `None`
+    /// if there are no observances to tell.
+    pub fn utc_offset(&self, local: NaiveDateTime) -> Option<FixedOffset> {
+        let latest = self
+            .observances
+            .iter()
+            .filter_map(|observance| Some((observance.last_onset(local)?, observance)))
+            .max_by_key(|(onset, _)| *onset);
+        match latest {
+            Some((_, observance)) => observance.offset("TZOFFSETTO"),
+            None => self
+                .observances
+                .iter()
+                .filter_map(|observance| Some((observance.onset()?, observance)))
+                .min_by_key(|(onset, _)| *onset)
+                .and_then(|(_, observance)| observance.offset("TZOFFSETFROM")),
+        }
+    }
+
+    /// Adds a STANDARD observance
+    pub fn standard(&mut self, observance: Observance) -> &mut Self {
+        self.append_observance(ObservanceKind::Standard, observance)
+    }
+
+    /// Adds a DAYLIGHT observance
+    pub fn daylight(&mut self, observance: Observance) -> &mut Self {
+        self.append_observance(ObservanceKind::Daylight, observance)
+    }
+
+    /// Adds an observance of the given kind
+    pub fn append_observance(
+        &mut self,
+        kind: ObservanceKind,
+        mut observance: Observance,
+    ) -> &mut Self {
+        observance.kind = kind;
+        self.observances.push(observance);
+        self
+    }
+
+    /// The `Timezone` as a `RawComponent`, its observances as sub-components
+    #[cfg(any(feature = "jcal", feature = "xcal"))]
+    pub(crate) fn to_raw(&self) -> RawComponent {
+        let mut raw = self.inner.to_raw("VTIMEZONE");
+        raw.components.extend(
+            self.observances
+                .iter()
+                .map(|observance| observance.inner.to_raw(observance.kind.as_str())),
+        );
+        raw
+    }
+}
+
+component_impl! { Timezone, "VTIMEZONE", {
+    /// Writes `Timezone` into a `Writer` using `std::fmt`, along with its observances.
+    ///
+    /// Time zones have no DTSTAMP and UID, so none are filled in.
+    fn fmt_write_with<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        _options: &WriteOptions,
+    ) -> Result<(), fmt::Error> {
+        write_crlf!(out, "BEGIN:VTIMEZONE")?;
+        self.inner.fmt_write_properties(out)?;
+        for observance in &self.observances {
+            observance.fmt_write(out)?;
+        }
+        write_crlf!(out, "END:VTIMEZONE")?;
+        Ok(())
+    }
+} }
+
+impl Observance {
+    /// Creates a new Observance.
+    ///
+    /// Whether it is a STANDARD or DAYLIGHT observance is decided when adding it to a
+    /// [`Timezone`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// End of builder pattern.
+    /// copies over everything
+    pub fn done(&mut self) -> Self {
+        Observance {
+            kind: self.kind,
+            inner: self.inner.done(),
+        }
+    }
+
+    /// Whether this is a STANDARD or DAYLIGHT observance
+    pub fn kind(&self) -> ObservanceKind {
+        self.kind
+    }
+
+    /// Read-only access to `properties`
+    pub fn properties(&self) -> &PropertyMap {
+        &self.inner.properties
+    }
+
+    /// Mutable access to `properties`
+    pub fn properties_mut(&mut self) -> &mut PropertyMap {
+        &mut self.inner.properties
+    }
+
+    /// Append a given `Property`
+    ///
+    /// It replaces one with the same key, unless there may be many such as RDATE.
+    pub fn append_property(&mut self, property: Property) -> &mut Self {
+        self.inner.properties.insert(property);
+        self
+    }
+
+    /// Adds a `Property` even if there already is one with the same key
+    pub fn append_multi_property(&mut self, property: Property) -> &mut Self {
+        self.inner.properties.push(property);
+        self
+    }
+
+    /// Construct and append a `Property`
+    pub fn add_property(&mut self, key: &str, val: &str) -> &mut Self {
+        self.append_property(Property::new(key, val))
+    }
+
+    /// Set the DTSTART `Property`, the local time the observance takes effect
+    pub fn starts(&mut self, start: NaiveDateTime) -> &mut Self {
+        self.append_property(DatePerhapsTime::from(start).to_property("DTSTART"))
+    }
+
+    /// Set the RRULE `Property`, usually a yearly rule such as the last sunday in March
+    pub fn repeats(&mut self, rule: RecurrenceRule) -> &mut Self {
+        self.append_property(rule.into())
+    }
+
+    /// Set the TZOFFSETFROM `Property`, the offset in use before this observance
+    pub fn offset_from(&mut self, offset: FixedOffset) -> &mut Self {
+        self.append_property(Property::with_value("TZOFFSETFROM", offset))
+    }
+
+    /// Set the TZOFFSETTO `Property`, the offset in use during this observance
+    pub fn offset_to(&mut self, offset: FixedOffset) -> &mut Self {
+        self.append_property(Property::with_value("TZOFFSETTO", offset))
+    }
+
+    /// Set the TZNAME `Property`, e.g. `CET`
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        self.append_property(Property::new("TZNAME", name))
+    }
+
+    /// The DTSTART of the observance
+    fn onset(&self) -> Option<NaiveDateTime> {
+        let start = self.inner.properties.get("DTSTART")?;
+        CalendarDateTime::parse(&start.value()).map(|start| start.naive())
+    }
+
+    /// The last time the observance started at or before `local`, taking RRULE and RDATE into
+    /// account
+    fn last_onset(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        Occurrences::new(
+            self.inner.properties.values(),
+            CalendarDateTime::Floating(self.onset()?),
+            CalendarDateTime::Floating(local + Duration::seconds(1)),
+        )
+        .last()
+        .map(|onset| onset.naive())
+    }
+
+    /// Writes `Observance` into a `Writer` using `std::fmt`, as STANDARD or DAYLIGHT depending
+    /// on its [`kind`](Observance::kind).
+    pub(crate) fn fmt_write<W: fmt::Write>(&self, out: &mut W) -> Result<(), fmt::Error> {
+        write_crlf!(out, "BEGIN:{}", self.kind.as_str())?;
+        self.inner.fmt_write_properties(out)?;
+        write_crlf!(out, "END:{}", self.kind.as_str())?;
+        Ok(())
+    }
+
+    /// Reads the TZOFFSETFROM or TZOFFSETTO `Property`
+    fn offset(&self, key: &str) -> Option<FixedOffset> {
+        match self.inner.properties.get(key)?.typed_value() {
+            Value::UtcOffset(offset) => Some(offset),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn utc_offsets() {
+        let offset = |seconds| format_utc_offset(FixedOffset::east_opt(seconds).unwrap());
+        assert_eq!(offset(3600), "+0100");
+        assert_eq!(offset(0), "+0000");
+        assert_eq!(offset(-(5 * 3600 + 30 * 60)), "-0530");
+        assert_eq!(offset(3600 + 30 * 60 + 45), "+013045");
+    }
+
+    #[test]
+    fn parse_utc_offsets() {
+        let offset = |seconds| FixedOffset::east_opt(seconds);
+        assert_eq!(parse_utc_offset("+0100"), offset(3600));
+        assert_eq!(parse_utc_offset("-0530"), offset(-(5 * 3600 + 30 * 60)));
+        assert_eq!(parse_utc_offset("+013045"), offset(3600 + 30 * 60 + 45));
+        assert_eq!(parse_utc_offset("0100"), None);
+        assert_eq!(parse_utc_offset("+01"), None);
+        assert_eq!(parse_utc_offset("+01:00"), None);
+    }
+
+    #[test]
+    fn utc_offset_of_recurring_observances() {
+        let at = |year, month, day, hour| {
+            NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let cet = FixedOffset::east_opt(3600).unwrap();
+        let cest = FixedOffset::east_opt(7200).unwrap();
+        let berlin = Timezone::new("Europe/Berlin")
+            .standard(
+                Observance::new()
+                    .starts(at(1996, 10, 27, 3))
+                    .offset_from(cest)
+                    .offset_to(cet)
+                    .repeats("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU".parse().unwrap())
+                    .done(),
+            )
+            .daylight(
+                Observance::new()
+                    .starts(at(1981, 3, 29, 2))
+                    .offset_from(cet)
+                    .offset_to(cest)
+                    .repeats("FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU".parse().unwrap())
+                    .done(),
+            )
+            .done();
+        assert_eq!(berlin.utc_offset(at(2021, 1, 15, 12)), Some(cet));
+        assert_eq!(berlin.utc_offset(at(2021, 7, 15, 12)), Some(cest));
+        assert_eq!(berlin.utc_offset(at(2021, 3, 28, 3)), Some(cest));
+        assert_eq!(berlin.utc_offset(at(2021, 10, 31, 4)), Some(cet));
+        // before the first observance
+        assert_eq!(berlin.utc_offset(at(1980, 1, 1, 0)), Some(cet));
+        assert_eq!(Timezone::new("Nowhere").utc_offset(at(2021, 1, 1, 0)), None);
+    }
+
+    #[test]
+    fn timezone_with_observances() {
+        let start = NaiveDate::from_ymd_opt(1970, 10, 25)
+            .unwrap()
+            .and_hms_opt(3, 0, 0)
+            .unwrap();
+        let timezone = Timezone::new("Europe/Berlin")
+            .standard(
+                Observance::new()
+                    .starts(start)
+                    .offset_from(FixedOffset::east_opt(7200).unwrap())
+                    .offset_to(FixedOffset::east_opt(3600).unwrap())
+                    .name("CET")
+                    .done(),
+            )
+            .done();
+        assert_eq!(timezone.tzid(), Some("Europe/Berlin"));
+        assert_eq!(
+            timezone.to_string(),
+            "BEGIN:VTIMEZONE\r\n\
+             TZID:Europe/Berlin\r\n\
+             BEGIN:STANDARD\r\n\
+             DTSTART:19701025T030000\r\n\
+             TZNAME:CET\r\n\
+             TZOFFSETFROM:+0200\r\n\
+             TZOFFSETTO:+0100\r\n\
+             END:STANDARD\r\n\
+             END:VTIMEZONE\r\n"
+        );
+    }
+}
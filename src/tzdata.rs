@@ -0,0 +1,300 @@
+//! Derives [`Timezone`] definitions from the IANA time zone database bundled with `chrono-tz`.
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDateTime, Offset as _, TimeZone, Timelike, Utc,
+};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+use crate::repeats::{Freq, RecurrenceRule, WeekdayNum};
+use crate::timezone::{Observance, ObservanceKind, Timezone};
+
+/// How far past the end of the requested range transitions are looked up, to find out whether
+/// a yearly rule is still in effect.
+const LOOKAHEAD_DAYS: i64 = 366;
+
+/// How far apart the offsets compared to find transitions are
+///
+/// `chrono-tz` does not expose its table of transitions, so they are found by comparing offsets
+/// and narrowing down where they differ. A change that is undone before the next step is missed,
+/// so the offsets are compared every day.
+const SCAN_STEP_DAYS: i64 = 1;
+
+/// Offset from UTC observed in a zone at some instant
+#[derive(Clone, Debug, PartialEq)]
+struct Offset {
+    utc_offset: FixedOffset,
+    daylight: bool,
+    name: Option<String>,
+}
+
+impl Offset {
+    fn at(tz: Tz, instant: NaiveDateTime) -> Self {
+        let offset = tz.offset_from_utc_datetime(&instant);
+        Offset {
+            utc_offset: offset.fix(),
+            daylight: !offset.dst_offset().is_zero(),
+            name: offset.abbreviation().map(ToOwned::to_owned),
+        }
+    }
+
+    fn kind(&self) -> ObservanceKind {
+        if self.daylight {
+            ObservanceKind::Daylight
+        } else {
+            ObservanceKind::Standard
+        }
+    }
+
+    /// The wall clock time at `instant` in this offset
+    fn local(&self, instant: NaiveDateTime) -> NaiveDateTime {
+        instant + Duration::seconds(i64::from(self.utc_offset.local_minus_utc()))
+    }
+
+    /// An observance starting at `instant`, coming from the offset `from`
+    fn observance(&self, from: &Offset, instant: NaiveDateTime) -> Observance {
+        let mut observance = Observance::new();
+        observance
+            .starts(from.local(instant))
+            .offset_from(from.utc_offset)
+            .offset_to(self.utc_offset);
+        if let Some(ref name) = self.name {
+            observance.name(name);
+        }
+        observance
+    }
+}
+
+/// A change of the observed offset
+#[derive(Debug)]
+struct Transition {
+    utc: NaiveDateTime,
+    from: Offset,
+    to: Offset,
+}
+
+/// Identifies transitions that follow the same yearly rule, such as
+/// "at 02:00 on the last sunday in march".
+#[derive(Debug, PartialEq)]
+struct RuleKey<'a> {
+    from: &'a Offset,
+    to: &'a Offset,
+    month: u32,
+    weekday: WeekdayNum,
+    time: (u32, u32, u32),
+}
+
+impl Transition {
+    fn local(&self) -> NaiveDateTime {
+        self.from.local(self.utc)
+    }
+
+    fn rule_key(&self) -> RuleKey<'_> {
+        let local = self.local();
+        let date = local.date();
+        let days_in_month = (28..=31)
+            .rev()
+            .find(|&day| date.with_day(day).is_some())
+            .unwrap_or(28);
+        // the last weekday of a month is preferred over the fourth or fifth
+        let weekday = if date.day() + 7 > days_in_month {
+            WeekdayNum::nth(-1, date.weekday())
+        } else {
+            WeekdayNum::nth(((date.day() - 1) / 7 + 1) as i8, date.weekday())
+        };
+        RuleKey {
+            from: &self.from,
+            to: &self.to,
+            month: date.month(),
+            weekday,
+            time: (local.hour(), local.minute(), local.second()),
+        }
+    }
+}
+
+/// Finds all changes of the observed offset between `start` and `end`, to the second.
+fn transitions(tz: Tz, start: NaiveDateTime, end: NaiveDateTime) -> Vec<Transition> {
+    let mut transitions = Vec::new();
+    let mut before = start;
+    let mut offset = Offset::at(tz, before);
+    while before < end {
+        let after = (before + Duration::days(SCAN_STEP_DAYS)).min(end);
+        let next = Offset::at(tz, after);
+        if next == offset {
+            before = after;
+            continue;
+        }
+        let (mut low, mut high) = (before, after);
+        while high - low > Duration::seconds(1) {
+            let middle = low + Duration::seconds((high - low).num_seconds() / 2);
+            if Offset::at(tz, middle) == offset {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let to = Offset::at(tz, high);
+        transitions.push(Transition {
+            utc: high,
+            from: offset,
+            to: to.clone(),
+        });
+        before = high;
+        offset = to;
+    }
+    transitions
+}
+
+impl Timezone {
+    /// Derives a `Timezone` from the IANA time zone database, covering `start` to `end`.
+    ///
+    /// The offset observed at `start` becomes the first observance. Transitions that recur
+    /// every year on the same weekday are combined into observances with a yearly RRULE, which
+    /// is left open ended if the rule is still in effect after `end`.
+    ///
+    /// Only available with the `chrono-tz` feature.
+    ///
+    /// ```rust
+    /// # use chrono::{TimeZone, Utc};
+    /// # use icalendar::Timezone;
+    /// let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    /// let end = Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+    /// let berlin = Timezone::from_tz(chrono_tz::Europe::Berlin, start, end);
+    /// assert_eq!(berlin.tzid(), Some("Europe/Berlin"));
+    /// assert_eq!(berlin.observances().len(), 3);
+    /// ```
+    pub fn from_tz(tz: Tz, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        let start = start
+            .naive_utc()
+            .with_nanosecond(0)
+            .unwrap_or(start.naive_utc());
+        let end = end.naive_utc().max(start);
+        let transitions = transitions(tz, start, end + Duration::days(LOOKAHEAD_DAYS));
+
+        // transitions following the same rule in consecutive years
+        let mut runs: Vec<Vec<&Transition>> = Vec::new();
+        for transition in &transitions {
+            let key = transition.rule_key();
+            let year = transition.local().year();
+            let run = runs.iter_mut().rev().find(|run| {
+                let last = run[run.len() - 1];
+                last.rule_key() == key && last.local().year() + 1 == year
+            });
+            match run {
+                Some(run) => run.push(transition),
+                None => runs.push(vec![transition]),
+            }
+        }
+
+        let mut timezone = Timezone::new(tz.name());
+        let initial = Offset::at(tz, start);
+        timezone.append_observance(initial.kind(), initial.observance(&initial, start));
+
+        for run in runs {
+            let (in_range, after): (Vec<_>, Vec<_>) = run
+                .into_iter()
+                .partition(|transition| transition.utc <= end);
+            let (first, last) = match (in_range.first(), in_range.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => continue,
+            };
+            let mut observance = first.to.observance(&first.from, first.utc);
+            if in_range.len() > 1 || !after.is_empty() {
+                let key = first.rule_key();
+                let mut rule = RecurrenceRule::new(Freq::Yearly);
+                rule.by_month(vec![key.month as u8])
+                    .by_day(vec![key.weekday]);
+                if after.is_empty() {
+                    rule.until(DateTime::<Utc>::from_naive_utc_and_offset(last.utc, Utc));
+                }
+                observance.repeats(rule);
+            }
+            timezone.append_observance(first.to.kind(), observance.done());
+        }
+        timezone.done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Component;
+    use pretty_assertions::assert_eq;
+
+    fn utc(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn transitions_to_the_second() {
+        let found = transitions(
+            chrono_tz::Europe::Berlin,
+            utc(2021, 1, 1).naive_utc(),
+            utc(2022, 1, 1).naive_utc(),
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].utc,
+            utc(2021, 3, 28).naive_utc() + Duration::hours(1)
+        );
+        assert_eq!(found[0].local().to_string(), "2021-03-28 02:00:00");
+        assert_eq!(found[1].local().to_string(), "2021-10-31 03:00:00");
+    }
+
+    #[test]
+    fn berlin_open_ended_rules() {
+        let berlin = Timezone::from_tz(chrono_tz::Europe::Berlin, utc(2020, 1, 1), utc(2023, 1, 1));
+        assert_eq!(
+            berlin.to_string(),
+            "BEGIN:VTIMEZONE\r\n\
+             TZID:Europe/Berlin\r\n\
+             BEGIN:STANDARD\r\n\
+             DTSTART:20200101T010000\r\n\
+             TZNAME:CET\r\n\
+             TZOFFSETFROM:+0100\r\n\
+             TZOFFSETTO:+0100\r\n\
+             END:STANDARD\r\n\
+             BEGIN:DAYLIGHT\r\n\
+             DTSTART:20200329T020000\r\n\
+             RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3\r\n\
+             TZNAME:CEST\r\n\
+             TZOFFSETFROM:+0100\r\n\
+             TZOFFSETTO:+0200\r\n\
+             END:DAYLIGHT\r\n\
+             BEGIN:STANDARD\r\n\
+             DTSTART:20201025T030000\r\n\
+             RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10\r\n\
+             TZNAME:CET\r\n\
+             TZOFFSETFROM:+0200\r\n\
+             TZOFFSETTO:+0100\r\n\
+             END:STANDARD\r\n\
+             END:VTIMEZONE\r\n"
+        );
+    }
+
+    #[test]
+    fn new_york_rule_change() {
+        let new_york = Timezone::from_tz(
+            chrono_tz::America::New_York,
+            utc(2005, 1, 1),
+            utc(2008, 1, 1),
+        )
+        .to_string();
+        assert!(new_york.contains(
+            "DTSTART:20050403T020000\r\nRRULE:FREQ=YEARLY;UNTIL=20060402T070000Z;BYDAY=1SU;BYMONTH=4\r\n"
+        ));
+        assert!(new_york.contains(
+            "DTSTART:20051030T020000\r\nRRULE:FREQ=YEARLY;UNTIL=20061029T060000Z;BYDAY=-1SU;BYMONTH=10\r\n"
+        ));
+        assert!(new_york
+            .contains("DTSTART:20070311T020000\r\nRRULE:FREQ=YEARLY;BYDAY=2SU;BYMONTH=3\r\n"));
+        assert!(new_york
+            .contains("DTSTART:20071104T020000\r\nRRULE:FREQ=YEARLY;BYDAY=1SU;BYMONTH=11\r\n"));
+    }
+
+    #[test]
+    fn zone_without_transitions() {
+        let tokyo = Timezone::from_tz(chrono_tz::Asia::Tokyo, utc(2020, 1, 1), utc(2030, 1, 1));
+        assert_eq!(tokyo.observances().len(), 1);
+        assert_eq!(tokyo.observances()[0].kind(), ObservanceKind::Standard);
+    }
+}
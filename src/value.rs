@@ -0,0 +1,313 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, Utc};
+
+use std::fmt;
+
+use crate::components::CalendarDateTime;
+use crate::period::{format_duration, parse_duration, Period};
+use crate::properties::ValueType;
+use crate::repeats::RecurrenceRule;
+use crate::timezone::{format_utc_offset, parse_utc_offset};
+
+/// The value of a [`Property`](crate::Property), one variant per [`ValueType`]
+///
+/// Values are rendered as RFC 5545 describes for their type, TEXT is escaped when the
+/// property is written. Conversions from the matching Rust types are provided in form of
+/// [From] implementations.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// BINARY, still encoded as base64
+    Binary(String),
+    /// BOOLEAN, `TRUE` or `FALSE`
+    Boolean(bool),
+    /// CAL-ADDRESS, e.g. `mailto:jane@example.com`
+    CalAddress(String),
+    /// DATE
+    Date(NaiveDate),
+    /// DATE-TIME, including the time zone of the `TZID` parameter
+    DateTime(CalendarDateTime),
+    /// DURATION, fractions of seconds are dropped
+    Duration(Duration),
+    /// FLOAT
+    Float(f64),
+    /// INTEGER
+    Integer(i32),
+    /// PERIOD
+    Period(Period),
+    /// RECUR, boxed as rules are large
+    Recur(Box<RecurrenceRule>),
+    /// TEXT, also any value as given to [`Property::new`](crate::Property::new)
+    Text(String),
+    /// TIME without time zone
+    Time(NaiveTime),
+    /// URI
+    Uri(String),
+    /// UTC-OFFSET
+    UtcOffset(FixedOffset),
+    /// Several values of the same type, such as the dates of EXDATE, rendered separated by `,`
+    List(Vec<Value>),
+}
+
+impl Default for Value {
+    fn default() -> Self {
+        Value::Text(String::new())
+    }
+}
+
+impl Value {
+    /// The type of this value, for a list the type of its first item
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::Binary(_) => ValueType::Binary,
+            Value::Boolean(_) => ValueType::Boolean,
+            Value::CalAddress(_) => ValueType::CalAddress,
+            Value::Date(_) => ValueType::Date,
+            Value::DateTime(_) => ValueType::DateTime,
+            Value::Duration(_) => ValueType::Duration,
+            Value::Float(_) => ValueType::Float,
+            Value::Integer(_) => ValueType::Integer,
+            Value::Period(_) => ValueType::Period,
+            Value::Recur(_) => ValueType::Recur,
+            Value::Text(_) => ValueType::Text,
+            Value::Time(_) => ValueType::Time,
+            Value::Uri(_) => ValueType::Uri,
+            Value::UtcOffset(_) => ValueType::UtcOffset,
+            Value::List(items) => items.first().map_or(ValueType::Text, Value::value_type),
+        }
+    }
+
+    /// The text of string-like values, which need no rendering
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Binary(text)
+            | Value::CalAddress(text)
+            | Value::Text(text)
+            | Value::Uri(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The `TZID` a date-time refers to, for a list that of its first item
+    pub(crate) fn tzid(&self) -> Option<&str> {
+        match self {
+            Value::DateTime(CalendarDateTime::WithTimezone { tzid, .. }) => Some(tzid),
+            Value::List(items) => items.first().and_then(Value::tzid),
+            _ => None,
+        }
+    }
+
+    /// Reads a single value of `value_type` as rendered by the `Display` implementation
+    ///
+    /// `tzid` is the `TZID` parameter that floating date-times refer to.
+    pub(crate) fn parse(value_type: ValueType, text: &str, tzid: Option<&str>) -> Option<Self> {
+        let value = match value_type {
+            ValueType::Binary => Value::Binary(text.to_owned()),
+            ValueType::Boolean => match text.to_uppercase().as_str() {
+                "TRUE" => Value::Boolean(true),
+                "FALSE" => Value::Boolean(false),
+                _ => return None,
+            },
+            ValueType::CalAddress => Value::CalAddress(text.to_owned()),
+            ValueType::Date => Value::Date(NaiveDate::parse_from_str(text, "%Y%m%d").ok()?),
+            ValueType::DateTime => Value::DateTime(match (CalendarDateTime::parse(text)?, tzid) {
+                (CalendarDateTime::Floating(date_time), Some(tzid)) => (date_time, tzid).into(),
+                (date_time, _) => date_time,
+            }),
+            ValueType::Duration => Value::Duration(parse_duration(text)?),
+            ValueType::Float => Value::Float(text.parse().ok()?),
+            ValueType::Integer => Value::Integer(text.parse().ok()?),
+            ValueType::Period => Value::Period(text.parse().ok()?),
+            ValueType::Recur => Value::Recur(Box::new(text.parse().ok()?)),
+            ValueType::Text => Value::Text(text.to_owned()),
+            ValueType::Time => Value::Time(NaiveTime::parse_from_str(text, "%H%M%S").ok()?),
+            ValueType::Uri => Value::Uri(text.to_owned()),
+            ValueType::UtcOffset => Value::UtcOffset(parse_utc_offset(text)?),
+        };
+        Some(value)
+    }
+}
+
+impl fmt::Display for Value {
+    /// Format the value in RFC 5545 compliant manner, TEXT unescaped.
+    ///
+    /// The `TZID` of a date-time is not part of the value, it is a parameter of the property.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Binary(text)
+            | Value::CalAddress(text)
+            | Value::Text(text)
+            | Value::Uri(text) => f.write_str(text),
+            Value::Boolean(true) => f.write_str("TRUE"),
+            Value::Boolean(false) => f.write_str("FALSE"),
+            Value::Date(date) => date.format("%Y%m%d").fmt(f),
+            Value::DateTime(date_time) => date_time.fmt(f),
+            Value::Duration(duration) => f.write_str(&format_duration(*duration)),
+            Value::Float(float) => float.fmt(f),
+            Value::Integer(integer) => integer.fmt(f),
+            Value::Period(period) => period.fmt(f),
+            Value::Recur(rule) => rule.fmt(f),
+            Value::Time(time) => time.format("%H%M%S").fmt(f),
+            Value::UtcOffset(offset) => f.write_str(&format_utc_offset(*offset)),
+            Value::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    item.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Values are serialized as their text, which is read back as [`Value::Text`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Value::Text)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        Value::Boolean(boolean)
+    }
+}
+
+impl From<NaiveDate> for Value {
+    fn from(date: NaiveDate) -> Self {
+        Value::Date(date)
+    }
+}
+
+impl From<CalendarDateTime> for Value {
+    fn from(date_time: CalendarDateTime) -> Self {
+        Value::DateTime(date_time)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(date_time: DateTime<Utc>) -> Self {
+        Value::DateTime(date_time.into())
+    }
+}
+
+impl From<Duration> for Value {
+    fn from(duration: Duration) -> Self {
+        Value::Duration(duration)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(float: f64) -> Self {
+        Value::Float(float)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(integer: i32) -> Self {
+        Value::Integer(integer)
+    }
+}
+
+impl From<Period> for Value {
+    fn from(period: Period) -> Self {
+        Value::Period(period)
+    }
+}
+
+impl From<RecurrenceRule> for Value {
+    fn from(rule: RecurrenceRule) -> Self {
+        Value::Recur(Box::new(rule))
+    }
+}
+
+impl From<NaiveTime> for Value {
+    fn from(time: NaiveTime) -> Self {
+        Value::Time(time)
+    }
+}
+
+impl From<FixedOffset> for Value {
+    fn from(offset: FixedOffset) -> Self {
+        Value::UtcOffset(offset)
+    }
+}
+
+impl<V: Into<Value>> From<Vec<V>> for Value {
+    fn from(items: Vec<V>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_and_display() {
+        for (value_type, text) in &[
+            (ValueType::Boolean, "TRUE"),
+            (ValueType::Date, "20210301"),
+            (ValueType::DateTime, "20210301T080000Z"),
+            (ValueType::DateTime, "20210301T080000"),
+            (ValueType::Duration, "-PT15M"),
+            (ValueType::Float, "37.386013"),
+            (ValueType::Integer, "-3"),
+            (ValueType::Period, "20210301T080000Z/20210301T090000Z"),
+            (ValueType::Recur, "FREQ=WEEKLY;COUNT=4"),
+            (ValueType::Time, "083000"),
+            (ValueType::UtcOffset, "-0500"),
+            (ValueType::Text, "a, b"),
+        ] {
+            let value = Value::parse(*value_type, text, None).unwrap();
+            assert_eq!(value.value_type(), *value_type);
+            assert_eq!(value.to_string(), *text);
+        }
+        assert_eq!(Value::parse(ValueType::Integer, "x", None), None);
+        assert_eq!(Value::parse(ValueType::Date, "20211301", None), None);
+    }
+
+    #[test]
+    fn date_time_in_time_zone() {
+        let value = Value::parse(
+            ValueType::DateTime,
+            "20210301T080000",
+            Some("Europe/Berlin"),
+        );
+        let naive = NaiveDate::from_ymd_opt(2021, 3, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        assert_eq!(
+            value,
+            Some(Value::DateTime((naive, "Europe/Berlin").into()))
+        );
+        assert_eq!(value.unwrap().tzid(), Some("Europe/Berlin"));
+
+        let utc = Utc.with_ymd_and_hms(2021, 3, 1, 8, 0, 0).unwrap();
+        let list = Value::from(vec![utc, utc]);
+        assert_eq!(list.value_type(), ValueType::DateTime);
+        assert_eq!(list.to_string(), "20210301T080000Z,20210301T080000Z");
+    }
+}
@@ -0,0 +1,526 @@
+//! Calendars as XML as described by xCal [(RFC 6321)](https://tools.ietf.org/html/rfc6321)
+
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event as XmlEvent};
+use quick_xml::{Reader, Writer};
+
+use std::io;
+
+use crate::calendar::Calendar;
+use crate::output::WriteOptions;
+use crate::parser::{build_calendar, ParseError, RawComponent};
+use crate::properties::{default_value_type, Parameter, Property, ValueType, LIST_PARAMETERS};
+use crate::representation::{
+    build_property, from_extended, items, join_recur, recur_parts, to_extended,
+};
+
+/// Namespace of all xCal elements
+const NAMESPACE: &str = "urn:ietf:params:xml:ns:icalendar-2.0";
+
+/// Elements holding the parts of a GEO value
+const GEO_PARTS: &[&str] = &["latitude", "longitude"];
+
+/// Elements holding the parts of a REQUEST-STATUS value
+const REQUEST_STATUS_PARTS: &[&str] = &["code", "description", "data"];
+
+impl Calendar {
+    /// Converts the calendar into an xCal document
+    ///
+    /// Every value is wrapped into an element named after its [`ValueType`], dates and times
+    /// are written like `2021-03-01T08:00:00Z`.
+    ///
+    /// ```rust
+    /// # use icalendar::*;
+    /// let mut calendar = Calendar::new();
+    /// calendar.push(Event::new().summary("Planning").priority(1).done());
+    ///
+    /// let xcal = calendar.to_xcal();
+    /// assert!(xcal.contains("<summary>\n"));
+    /// assert!(xcal.contains("<text>Planning</text>"));
+    /// assert!(xcal.contains("<integer>1</integer>"));
+    ///
+    /// let parsed = Calendar::from_xcal(&xcal).unwrap();
+    /// assert_eq!(parsed.len(), 1);
+    /// ```
+    pub fn to_xcal(&self) -> String {
+        self.to_xcal_with(&WriteOptions::default())
+    }
+
+    /// Like [`to_xcal`](Calendar::to_xcal), with a missing DTSTAMP and UID of the components
+    /// filled in as `options` say
+    pub fn to_xcal_with(&self, options: &WriteOptions) -> String {
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        write_document(&mut writer, &self.to_raw(options))
+            .expect("writing into memory does not fail");
+        String::from_utf8(writer.into_inner()).expect("all written text is UTF-8")
+    }
+
+    /// Reads a calendar from an xCal document
+    ///
+    /// The components of all `vcalendar` elements are kept or skipped just like
+    /// [`Calendar::from_str`](std::str::FromStr) does.
+    pub fn from_xcal(xml: &str) -> Result<Calendar, ParseError> {
+        let root = read_document(xml)?;
+        if root.name != "icalendar" {
+            return Err(ParseError::new(format!(
+                "expected icalendar, found {}",
+                root.name
+            )));
+        }
+        let mut calendar = Calendar::new();
+        for vcalendar in root.children("vcalendar") {
+            let mut read = build_calendar(component_from_xcal(vcalendar)?);
+            for property in read.properties.drain(..) {
+                calendar.append_property(property);
+            }
+            calendar.append(&mut read);
+        }
+        Ok(calendar)
+    }
+}
+
+fn start<W: io::Write>(writer: &mut Writer<W>, name: &str) -> io::Result<()> {
+    writer.write_event(XmlEvent::Start(BytesStart::new(name)))
+}
+
+fn end<W: io::Write>(writer: &mut Writer<W>, name: &str) -> io::Result<()> {
+    writer.write_event(XmlEvent::End(BytesEnd::new(name)))
+}
+
+fn text_element<W: io::Write>(writer: &mut Writer<W>, name: &str, text: &str) -> io::Result<()> {
+    start(writer, name)?;
+    writer.write_event(XmlEvent::Text(BytesText::new(text)))?;
+    end(writer, name)
+}
+
+fn write_document<W: io::Write>(writer: &mut Writer<W>, calendar: &RawComponent) -> io::Result<()> {
+    writer.write_event(XmlEvent::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+    let mut root = BytesStart::new("icalendar");
+    root.push_attribute(("xmlns", NAMESPACE));
+    writer.write_event(XmlEvent::Start(root))?;
+    write_component(writer, calendar)?;
+    end(writer, "icalendar")
+}
+
+fn write_component<W: io::Write>(writer: &mut Writer<W>, raw: &RawComponent) -> io::Result<()> {
+    let name = raw.kind.to_lowercase();
+    start(writer, &name)?;
+    start(writer, "properties")?;
+    for property in &raw.properties {
+        write_property(writer, property)?;
+    }
+    end(writer, "properties")?;
+    if !raw.components.is_empty() {
+        start(writer, "components")?;
+        for component in &raw.components {
+            write_component(writer, component)?;
+        }
+        end(writer, "components")?;
+    }
+    end(writer, &name)
+}
+
+fn write_property<W: io::Write>(writer: &mut Writer<W>, property: &Property) -> io::Result<()> {
+    let name = property.key().to_lowercase();
+    let value_type = property.value_type();
+    start(writer, &name)?;
+
+    // the type is given by the elements of the values
+    let mut parameters = property
+        .parameters()
+        .iter()
+        .filter(|parameter| parameter.key() != "VALUE")
+        .peekable();
+    if parameters.peek().is_some() {
+        start(writer, "parameters")?;
+        for parameter in parameters {
+            write_parameter(writer, parameter)?;
+        }
+        end(writer, "parameters")?;
+    }
+
+    let structure = match property.key() {
+        "GEO" => Some(GEO_PARTS),
+        "REQUEST-STATUS" => Some(REQUEST_STATUS_PARTS),
+        _ => None,
+    };
+    if let Some(structure) = structure {
+        for (part, value) in structure.iter().zip(property.value().split(';')) {
+            text_element(writer, part, value)?;
+        }
+    } else {
+        for item in items(property, value_type) {
+            write_value(writer, value_type, &item)?;
+        }
+    }
+    end(writer, &name)
+}
+
+fn write_parameter<W: io::Write>(writer: &mut Writer<W>, parameter: &Parameter) -> io::Result<()> {
+    let name = parameter.key().to_lowercase();
+    let value_type = parameter_type(parameter.key());
+    start(writer, &name)?;
+    if LIST_PARAMETERS.contains(&parameter.key()) {
+        for item in parameter.value().split(',') {
+            text_element(writer, value_type, item)?;
+        }
+    } else if value_type == "boolean" {
+        text_element(writer, value_type, &parameter.value().to_lowercase())?;
+    } else {
+        text_element(writer, value_type, parameter.value())?;
+    }
+    end(writer, &name)
+}
+
+/// The type of the value of a parameter as given by RFC 6321
+fn parameter_type(key: &str) -> &'static str {
+    match key {
+        "DELEGATED-FROM" | "DELEGATED-TO" | "MEMBER" | "SENT-BY" => "cal-address",
+        "ALTREP" | "DIR" => "uri",
+        "RSVP" => "boolean",
+        _ => "text",
+    }
+}
+
+fn write_value<W: io::Write>(
+    writer: &mut Writer<W>,
+    value_type: ValueType,
+    value: &str,
+) -> io::Result<()> {
+    match value_type {
+        ValueType::Recur => {
+            start(writer, "recur")?;
+            for (name, items) in recur_parts(value) {
+                for item in items {
+                    text_element(writer, &name.to_lowercase(), &item)?;
+                }
+            }
+            end(writer, "recur")
+        }
+        ValueType::Period => match value.split_once('/') {
+            Some((period_start, period_end)) => {
+                start(writer, "period")?;
+                text_element(
+                    writer,
+                    "start",
+                    &to_extended(ValueType::DateTime, period_start),
+                )?;
+                if period_end.starts_with('P') {
+                    text_element(writer, "duration", period_end)?;
+                } else {
+                    text_element(writer, "end", &to_extended(ValueType::DateTime, period_end))?;
+                }
+                end(writer, "period")
+            }
+            None => text_element(writer, "period", value),
+        },
+        value_type => text_element(
+            writer,
+            &value_type.as_str().to_lowercase(),
+            &to_extended(value_type, value),
+        ),
+    }
+}
+
+/// An element of the document, only what xCal makes use of
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    text: String,
+    elements: Vec<Element>,
+}
+
+impl Element {
+    fn new(start: &BytesStart<'_>) -> Self {
+        Element {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).to_lowercase(),
+            ..Default::default()
+        }
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements
+            .iter()
+            .filter(move |element| element.name == name)
+    }
+
+    fn child<'a>(&'a self, name: &'a str) -> Option<&'a Element> {
+        self.children(name).next()
+    }
+}
+
+fn xml_error(error: impl std::fmt::Display) -> ParseError {
+    ParseError::new(format!("invalid xCal document: {}", error))
+}
+
+/// Reads the whole document into a tree of `Element`s, returning the root
+fn read_document(xml: &str) -> Result<Element, ParseError> {
+    let mut reader = Reader::from_str(xml);
+    let mut open: Vec<Element> = Vec::new();
+    loop {
+        let finished = match reader.read_event().map_err(xml_error)? {
+            XmlEvent::Start(start) => {
+                open.push(Element::new(&start));
+                continue;
+            }
+            XmlEvent::Empty(start) => Element::new(&start),
+            XmlEvent::End(_) => match open.pop() {
+                // the text between child elements is only indentation, values are kept as they are
+                Some(mut element) if !element.elements.is_empty() => {
+                    element.text.clear();
+                    element
+                }
+                Some(element) => element,
+                None => return Err(xml_error("unexpected end tag")),
+            },
+            XmlEvent::Text(text) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&text.unescape().map_err(xml_error)?);
+                }
+                continue;
+            }
+            XmlEvent::CData(data) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&data.decode().map_err(xml_error)?);
+                }
+                continue;
+            }
+            XmlEvent::Eof => return Err(xml_error("no root element")),
+            _ => continue,
+        };
+        match open.last_mut() {
+            Some(parent) => parent.elements.push(finished),
+            None => return Ok(finished),
+        }
+    }
+}
+
+fn component_from_xcal(element: &Element) -> Result<RawComponent, ParseError> {
+    let mut raw = RawComponent::new(element.name.to_uppercase());
+    for properties in element.children("properties") {
+        for property in &properties.elements {
+            raw.properties.push(property_from_xcal(property)?);
+        }
+    }
+    for components in element.children("components") {
+        for component in &components.elements {
+            raw.components.push(component_from_xcal(component)?);
+        }
+    }
+    Ok(raw)
+}
+
+/// Joins the parts of a structured value such as GEO
+fn structured_from_xcal(element: &Element, parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| element.child(part))
+        .map(|part| part.text.as_str())
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn property_from_xcal(element: &Element) -> Result<Property, ParseError> {
+    let key = element.name.to_uppercase();
+    let (value_type, items) = match key.as_str() {
+        "GEO" => (
+            ValueType::Float,
+            vec![structured_from_xcal(element, GEO_PARTS)],
+        ),
+        "REQUEST-STATUS" => (
+            ValueType::Text,
+            vec![structured_from_xcal(element, REQUEST_STATUS_PARTS)],
+        ),
+        _ => {
+            let first = element
+                .elements
+                .iter()
+                .find(|child| child.name != "parameters")
+                .ok_or_else(|| ParseError::new(format!("xCal property {} has no value", key)))?;
+            let value_type = if first.name == "unknown" {
+                default_value_type(&key)
+            } else {
+                first.name.parse()?
+            };
+            let items = element
+                .children(&first.name)
+                .map(|value| match value_type {
+                    ValueType::Recur => recur_from_xcal(value),
+                    ValueType::Period => period_from_xcal(value),
+                    value_type => from_extended(value_type, &value.text),
+                })
+                .collect();
+            (value_type, items)
+        }
+    };
+
+    let mut property = build_property(&key, value_type, items);
+    for parameters in element.children("parameters") {
+        for parameter in &parameters.elements {
+            property.append_parameter(parameter_from_xcal(parameter));
+        }
+    }
+    Ok(property)
+}
+
+fn parameter_from_xcal(element: &Element) -> Parameter {
+    let key = element.name.to_uppercase();
+    let items: Vec<String> = element
+        .elements
+        .iter()
+        .map(|value| match value.name.as_str() {
+            "boolean" => value.text.to_uppercase(),
+            _ => value.text.clone(),
+        })
+        .collect();
+    let value = match items.as_slice() {
+        // a value without element around it
+        [] => element.text.clone(),
+        items => items.join(","),
+    };
+    Parameter::new(&key, &value)
+}
+
+fn period_from_xcal(element: &Element) -> String {
+    let start = element.child("start").map(|start| start.text.as_str());
+    let end = element.child("end").map(|end| end.text.as_str());
+    let duration = element
+        .child("duration")
+        .map(|duration| duration.text.as_str());
+    match (start, end, duration) {
+        (Some(start), Some(end), _) => format!(
+            "{}/{}",
+            from_extended(ValueType::DateTime, start),
+            from_extended(ValueType::DateTime, end)
+        ),
+        (Some(start), None, Some(duration)) => {
+            format!("{}/{}", from_extended(ValueType::DateTime, start), duration)
+        }
+        _ => from_extended(ValueType::Period, &element.text),
+    }
+}
+
+fn recur_from_xcal(element: &Element) -> String {
+    let mut parts: Vec<(String, Vec<String>)> = Vec::new();
+    for part in &element.elements {
+        match parts.iter_mut().find(|(name, _)| *name == part.name) {
+            Some((_, items)) => items.push(part.text.clone()),
+            None => parts.push((part.name.clone(), vec![part.text.clone()])),
+        }
+    }
+    join_recur(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(property: &Property) -> String {
+        let mut writer = Writer::new(Vec::new());
+        write_property(&mut writer, property).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    fn round_trip(property: &Property) -> Property {
+        let xml = written(property);
+        property_from_xcal(&read_document(&xml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn values_by_type() {
+        let xml = |key: &str, value: &str| written(&Property::new(key, value));
+        assert_eq!(
+            xml("DTSTART", "20210301T080000Z"),
+            "<dtstart><date-time>2021-03-01T08:00:00Z</date-time></dtstart>"
+        );
+        assert_eq!(
+            xml("GEO", "37.386013;-122.082932"),
+            "<geo><latitude>37.386013</latitude><longitude>-122.082932</longitude></geo>"
+        );
+        assert_eq!(
+            xml("CATEGORIES", "Work,Fun\\, mostly & more"),
+            "<categories><text>Work</text><text>Fun, mostly &amp; more</text></categories>"
+        );
+        assert_eq!(
+            xml("FREEBUSY", "20210301T080000Z/PT1H"),
+            "<freebusy><period><start>2021-03-01T08:00:00Z</start>\
+             <duration>PT1H</duration></period></freebusy>"
+        );
+        assert_eq!(
+            xml("RRULE", "FREQ=WEEKLY;BYDAY=MO,TU"),
+            "<rrule><recur><freq>WEEKLY</freq><byday>MO</byday><byday>TU</byday></recur></rrule>"
+        );
+        let attendee = Property::new("ATTENDEE", "mailto:jane@example.com")
+            .add_parameter("RSVP", "TRUE")
+            .add_parameter("DELEGATED-TO", "mailto:a@example.com")
+            .done();
+        assert_eq!(
+            written(&attendee),
+            "<attendee><parameters>\
+             <rsvp><boolean>true</boolean></rsvp>\
+             <delegated-to><cal-address>mailto:a@example.com</cal-address></delegated-to>\
+             </parameters>\
+             <cal-address>mailto:jane@example.com</cal-address></attendee>"
+        );
+    }
+
+    #[test]
+    fn values_round_trip() {
+        for (key, value) in &[
+            ("DTSTART", "20210301T080000Z"),
+            ("EXDATE", "20210301T080000,20210308T080000"),
+            ("GEO", "37.386013;-122.082932"),
+            ("CATEGORIES", "Work,Fun\\, mostly"),
+            (
+                "RRULE",
+                "FREQ=WEEKLY;BYDAY=MO,TU;COUNT=5;UNTIL=20211231T000000Z",
+            ),
+            (
+                "FREEBUSY",
+                "20210301T080000Z/PT1H,20210301T100000Z/20210301T110000Z",
+            ),
+            ("TZOFFSETTO", "-0500"),
+            ("REQUEST-STATUS", "2.0;Success"),
+            ("SUMMARY", "<Planning> & \"more\""),
+        ] {
+            assert_eq!(round_trip(&Property::new(key, value)).value(), *value);
+        }
+
+        let attendee = Property::new("ATTENDEE", "mailto:jane@example.com")
+            .add_parameter("RSVP", "TRUE")
+            .add_parameter("DELEGATED-TO", "mailto:a@example.com,mailto:b@example.com")
+            .done();
+        let read = round_trip(&attendee);
+        assert_eq!(read.parameter("RSVP").unwrap().value(), "TRUE");
+        assert_eq!(
+            read.parameter("DELEGATED-TO").unwrap().value(),
+            "mailto:a@example.com,mailto:b@example.com"
+        );
+
+        let date = round_trip(
+            &Property::new("DTSTART", "20210301")
+                .append_parameter(ValueType::Date)
+                .done(),
+        );
+        assert_eq!(date.value(), "20210301");
+        assert_eq!(date.value_type(), ValueType::Date);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Calendar::from_xcal("").is_err());
+        assert!(Calendar::from_xcal("<icalendar><vcalendar>").is_err());
+        assert!(Calendar::from_xcal("<vcalendar/>").is_err());
+        assert!(Calendar::from_xcal(
+            "<icalendar><vcalendar><properties><x-foo><nope>1</nope></x-foo>\
+             </properties></vcalendar></icalendar>"
+        )
+        .is_err());
+        let empty = Calendar::from_xcal(
+            "<icalendar xmlns=\"urn:ietf:params:xml:ns:icalendar-2.0\">\
+             <vcalendar><components><vevent><properties/></vevent></components></vcalendar>\
+             </icalendar>",
+        )
+        .unwrap();
+        assert_eq!(empty.len(), 1);
+    }
+}
@@ -1,5 +1,10 @@
 use chrono::prelude::*;
-use icalendar::{Calendar, Class, Component, Event, EventStatus, Todo};
+use icalendar::{
+    Attendee, AttendeeRole, Calendar, CalendarDateTime, CalendarElement, Class, Component,
+    DatePerhapsTime, Event, EventStatus, FreeBusyType, Journal, JournalStatus, Observance,
+    Organizer, ParticipationStatus, Period, Property, Reader, Timezone, Todo, TodoStatus,
+    WriteOptions,
+};
 use pretty_assertions::assert_eq;
 
 const EXPECTED_CAL_CONTENT: &str = "\
@@ -33,10 +38,11 @@ END:VCALENDAR\r
 #[test]
 fn test_calendar_to_string() {
     let mut calendar = Calendar::new();
-    let cest_date = FixedOffset::east(2 * 3600)
-        .ymd(2014, 7, 8)
-        .and_hms(9, 10, 11);
-    let utc_date = Utc.ymd(2014, 7, 9).and_hms(9, 10, 11);
+    let cest_date = FixedOffset::east_opt(2 * 3600)
+        .unwrap()
+        .with_ymd_and_hms(2014, 7, 8, 9, 10, 11)
+        .unwrap();
+    let utc_date = Utc.with_ymd_and_hms(2014, 7, 9, 9, 10, 11).unwrap();
     let event = Event::new()
         .status(EventStatus::Tentative)
         .starts(cest_date.with_timezone(&Utc))
@@ -61,3 +67,740 @@ fn test_calendar_to_string() {
     calendar.push(todo);
     assert_eq!(calendar.to_string(), EXPECTED_CAL_CONTENT);
 }
+
+#[test]
+fn test_calendar_from_str() {
+    let calendar: Calendar = EXPECTED_CAL_CONTENT.parse().unwrap();
+    assert_eq!(calendar.len(), 2);
+    assert_eq!(calendar.to_string(), EXPECTED_CAL_CONTENT);
+}
+
+#[test]
+fn test_timezone_qualified_date_time() {
+    let local = NaiveDate::from_ymd_opt(2021, 3, 28)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+    let event = Event::new()
+        .starts((local, "Europe/Berlin"))
+        .ends(CalendarDateTime::WithTimezone {
+            date_time: local + chrono::Duration::hours(1),
+            tzid: "Europe/Berlin".into(),
+        })
+        .uid("tzuid")
+        .add_property("DTSTAMP", "20210301T000000Z")
+        .done();
+    let mut calendar = Calendar::new();
+    calendar.push(event);
+    calendar.push(Timezone::new("Europe/Berlin"));
+
+    let ics = calendar.to_string();
+    assert!(ics.contains("\r\nDTSTART;TZID=Europe/Berlin:20210328T100000\r\n"));
+    assert!(ics.contains("\r\nDTEND;TZID=Europe/Berlin:20210328T110000\r\n"));
+    assert!(ics.find("BEGIN:VTIMEZONE").unwrap() < ics.find("BEGIN:VEVENT").unwrap());
+}
+
+#[test]
+fn test_journal_with_many_descriptions() {
+    let journal = Journal::new()
+        .summary("Daily notes")
+        .starts(
+            NaiveDate::from_ymd_opt(2021, 3, 1)
+                .unwrap()
+                .and_hms_opt(18, 0, 0)
+                .unwrap(),
+        )
+        .status(JournalStatus::Final)
+        .description("Nothing yet")
+        .description("Fixed the build")
+        .add_description("Reviewed the parser")
+        .uid("journaluid")
+        .add_property("DTSTAMP", "20210301T180000Z")
+        .done();
+    let mut calendar = Calendar::new();
+    calendar.push(journal);
+
+    let ics = calendar.to_string();
+    assert!(ics.contains(
+        "BEGIN:VJOURNAL\r\n\
+         DESCRIPTION:Fixed the build\r\n\
+         DESCRIPTION:Reviewed the parser\r\n\
+         DTSTAMP:20210301T180000Z\r\n"
+    ));
+    assert!(ics.contains(
+        "UID:journaluid\r\n\
+         END:VJOURNAL\r\n"
+    ));
+    assert!(!ics.contains("Nothing yet"));
+    assert!(ics.contains("STATUS:FINAL\r\n"));
+    assert_eq!(ics.parse::<Calendar>().unwrap().to_string(), ics);
+}
+
+#[test]
+fn test_meeting_invite_round_trip() {
+    let attendees = vec![
+        Attendee::new("jane@example.com")
+            .common_name("Doe, Jane")
+            .role(AttendeeRole::Chair)
+            .participation_status(ParticipationStatus::Accepted)
+            .done(),
+        Attendee::new("john@example.com")
+            .rsvp(true)
+            .delegated_from("joe@example.com")
+            .done(),
+    ];
+    let mut event = Event::new();
+    event.summary("Planning").organizer(
+        Organizer::new("boss@example.com")
+            .sent_by("assistant@example.com")
+            .done(),
+    );
+    for attendee in &attendees {
+        event.attendee(attendee.clone());
+    }
+
+    let ics = Component::to_string(&event);
+    assert!(ics.contains(
+        "ORGANIZER;SENT-BY=\"mailto:assistant@example.com\":mailto:boss@example.com\r\n"
+    ));
+    assert!(ics.contains("CN=\"Doe, Jane\""));
+
+    let parsed: Event = ics.parse().unwrap();
+    assert_eq!(parsed.attendees(), attendees);
+}
+
+#[test]
+fn test_free_busy() {
+    let utc = |day, hour, minute| Utc.with_ymd_and_hms(2021, 3, day, hour, minute, 0).unwrap();
+    let berlin = Timezone::new("Europe/Berlin")
+        .standard(
+            Observance::new()
+                .starts(
+                    NaiveDate::from_ymd_opt(1970, 10, 25)
+                        .unwrap()
+                        .and_hms_opt(3, 0, 0)
+                        .unwrap(),
+                )
+                .offset_from(FixedOffset::east_opt(7200).unwrap())
+                .offset_to(FixedOffset::east_opt(3600).unwrap())
+                .repeats("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU".parse().unwrap())
+                .done(),
+        )
+        .daylight(
+            Observance::new()
+                .starts(
+                    NaiveDate::from_ymd_opt(1970, 3, 29)
+                        .unwrap()
+                        .and_hms_opt(2, 0, 0)
+                        .unwrap(),
+                )
+                .offset_from(FixedOffset::east_opt(3600).unwrap())
+                .offset_to(FixedOffset::east_opt(7200).unwrap())
+                .repeats("FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU".parse().unwrap())
+                .done(),
+        )
+        .done();
+    let local = NaiveDate::from_ymd_opt(2021, 3, 3)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+
+    let mut calendar = Calendar::new();
+    calendar.push(berlin);
+    calendar.push(
+        Event::new()
+            .summary("weekly")
+            .starts(utc(1, 9, 0))
+            .ends(utc(1, 10, 0))
+            .repeats("FREQ=WEEKLY;COUNT=3".parse().unwrap())
+            .done(),
+    );
+    calendar.push(
+        Event::new()
+            .starts(utc(1, 9, 30))
+            .ends(utc(1, 11, 0))
+            .done(),
+    );
+    calendar.push(
+        Event::new()
+            .starts(utc(2, 9, 0))
+            .ends(utc(2, 18, 0))
+            .add_property("TRANSP", "TRANSPARENT")
+            .done(),
+    );
+    calendar.push(
+        Event::new()
+            .starts(utc(4, 9, 0))
+            .ends(utc(4, 10, 0))
+            .status(EventStatus::Cancelled)
+            .done(),
+    );
+    calendar.push(
+        Event::new()
+            .starts(utc(2, 14, 0))
+            .add_property("DURATION", "PT1H")
+            .status(EventStatus::Tentative)
+            .done(),
+    );
+    calendar.push(
+        Event::new()
+            .starts((local, "Europe/Berlin"))
+            .ends((local + chrono::Duration::hours(1), "Europe/Berlin"))
+            .done(),
+    );
+    // ends at the same wall clock time, but an hour later
+    calendar.push(
+        Event::new()
+            .starts((local + chrono::Duration::days(2), "Europe/Berlin"))
+            .ends(utc(5, 10, 0))
+            .done(),
+    );
+    // no Timezone tells the offset
+    calendar.push(
+        Event::new()
+            .starts((local + chrono::Duration::days(3), "Not/A_Zone"))
+            .ends((local + chrono::Duration::days(4), "Not/A_Zone"))
+            .done(),
+    );
+
+    let free_busy = calendar.free_busy(utc(1, 0, 0), utc(9, 0, 0));
+    let ics = Component::to_string(&free_busy);
+    assert!(ics.contains("DTSTART:20210301T000000Z\r\n"));
+    assert!(ics.contains("DTEND:20210309T000000Z\r\n"));
+    assert!(ics.contains("FREEBUSY;FBTYPE=BUSY-TENTATIVE:20210302T140000Z/20210302T150000Z\r\n"));
+    assert!(!ics.contains("SUMMARY"));
+
+    assert_eq!(
+        free_busy.periods(),
+        vec![
+            (FreeBusyType::Busy, Period::new(utc(1, 9, 0), utc(1, 11, 0))),
+            // 10:00 in Berlin
+            (FreeBusyType::Busy, Period::new(utc(3, 9, 0), utc(3, 10, 0))),
+            (FreeBusyType::Busy, Period::new(utc(5, 9, 0), utc(5, 10, 0))),
+            (FreeBusyType::Busy, Period::new(utc(8, 9, 0), utc(8, 10, 0))),
+            (
+                FreeBusyType::BusyTentative,
+                Period::new(utc(2, 14, 0), utc(2, 15, 0))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_text_escaping_round_trip() {
+    let summary = "Lunch; Coffee, Cake\\Pie";
+    let description = "Bring:\n- plates, cups\n- a \"good\" mood";
+    let event = Event::new()
+        .summary(summary)
+        .description(description)
+        .location("Room 1, Floor 2")
+        .done();
+    let ics = event.to_string();
+    assert!(ics.contains("SUMMARY:Lunch\\; Coffee\\, Cake\\\\Pie\r\n"));
+    assert!(ics.contains("LOCATION:Room 1\\, Floor 2\r\n"));
+
+    let parsed: Event = ics.parse().unwrap();
+    assert_eq!(parsed.properties()["SUMMARY"].value(), summary);
+    assert_eq!(parsed.properties()["DESCRIPTION"].value(), description);
+    assert_eq!(parsed.properties()["LOCATION"].value(), "Room 1, Floor 2");
+}
+
+#[test]
+fn test_stream_round_trip() {
+    let mut calendar: Calendar = (0..500)
+        .map(|i| {
+            Event::new()
+                .summary(&format!("Meeting #{}", i))
+                .description(&"A long, long agenda; ".repeat(10))
+                .done()
+        })
+        .collect();
+    calendar.push(Todo::new().summary("Agenda").done());
+    calendar.ensure_uids(&WriteOptions::new());
+
+    let mut ics = Vec::new();
+    calendar.write_to(&mut ics).unwrap();
+    let read = Reader::new(std::io::BufReader::new(&ics[..]))
+        .components()
+        .collect::<Result<Calendar, _>>()
+        .unwrap();
+    assert_eq!(read.len(), 501);
+    assert_eq!(read.to_string(), calendar.to_string());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    use chrono::Duration;
+    use icalendar::{Alarm, Venue};
+
+    let mut calendar = Calendar::new();
+    calendar
+        .push(
+            Event::new()
+                .summary("Planning; with semicolons")
+                .status(EventStatus::Confirmed)
+                .class(Class::Private)
+                .attendee(
+                    Attendee::new("mailto:jane@example.com")
+                        .common_name("Jane")
+                        .done(),
+                )
+                .done(),
+        )
+        .push(
+            Todo::new()
+                .summary("Agenda")
+                .status(TodoStatus::NeedsAction)
+                .done(),
+        )
+        .push(Venue::new().street_address("Main Street 1").done());
+    calendar.ensure_uids(&WriteOptions::new());
+
+    let json = serde_json::to_string(&calendar).unwrap();
+    let read: Calendar = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.len(), 3);
+    assert_eq!(read.to_string(), calendar.to_string());
+
+    let event = Event::new()
+        .summary("Planning")
+        .alarm(Alarm::display("Soon", -Duration::minutes(5)))
+        .done();
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        serde_json::json!({
+            "properties": [{"key": "SUMMARY", "value": "Planning", "parameters": []}],
+            "alarms": [{"properties": [
+                {"key": "ACTION", "value": "DISPLAY", "parameters": []},
+                {"key": "DESCRIPTION", "value": "Soon", "parameters": []},
+                {"key": "TRIGGER", "value": "-PT5M", "parameters": []},
+            ]}],
+        })
+    );
+    let read: Event = serde_json::from_value(serde_json::json!({
+        "properties": [
+            {"key": "SUMMARY", "value": "Draft", "parameters": []},
+            {"key": "SUMMARY", "value": "Planning", "parameters": []},
+            {"key": "CATEGORIES", "value": "WORK", "parameters": []},
+            {"key": "CATEGORIES", "value": "MEETING", "parameters": []},
+        ],
+    }))
+    .unwrap();
+    assert_eq!(read.get_summary(), Some("Planning"));
+    assert_eq!(read.properties().get_all("CATEGORIES").len(), 2);
+    assert!(read.alarms().is_empty());
+
+    let timezone = Timezone::new("Europe/Berlin")
+        .daylight(
+            Observance::new()
+                .offset_from(FixedOffset::east_opt(3600).unwrap())
+                .offset_to(FixedOffset::east_opt(7200).unwrap())
+                .done(),
+        )
+        .done();
+    let json = serde_json::to_string(&timezone).unwrap();
+    assert!(json.contains("\"observances\":[{\"kind\":\"DAYLIGHT\","));
+    let read: Timezone = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.to_string(), timezone.to_string());
+
+    assert_eq!(
+        serde_json::to_string(&EventStatus::Confirmed).unwrap(),
+        "\"CONFIRMED\""
+    );
+    assert_eq!(
+        serde_json::from_str::<TodoStatus>("\"NEEDS-ACTION\"").unwrap() as u8,
+        TodoStatus::NeedsAction as u8
+    );
+}
+
+#[cfg(feature = "jcal")]
+#[test]
+fn test_jcal_round_trip() {
+    use chrono::Duration;
+    use icalendar::{Alarm, Related, ValueType};
+
+    let start = NaiveDate::from_ymd_opt(2021, 3, 1)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let mut calendar = Calendar::new();
+    calendar
+        .push(
+            Timezone::new("Europe/Berlin")
+                .standard(
+                    Observance::new()
+                        .starts(start)
+                        .offset_from(FixedOffset::east_opt(7200).unwrap())
+                        .offset_to(FixedOffset::east_opt(3600).unwrap())
+                        .repeats("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU".parse().unwrap())
+                        .done(),
+                )
+                .done(),
+        )
+        .push(
+            Event::new()
+                .summary("Planning, again")
+                .starts((start, "Europe/Berlin"))
+                .repeats("FREQ=WEEKLY;COUNT=4".parse().unwrap())
+                .priority(1)
+                .alarm(Alarm::display("Soon", (-Duration::minutes(15), Related::Start)).done())
+                .done(),
+        )
+        .push(
+            Event::new()
+                .append_property(
+                    Property::new("DTSTART", "20210302")
+                        .append_parameter(ValueType::Date)
+                        .done(),
+                )
+                .add_property("CATEGORIES", "Holiday,Out\\, of office")
+                .done(),
+        );
+    let options = WriteOptions::deterministic(Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap());
+
+    let jcal = calendar.to_jcal_with(&options);
+    assert_eq!(jcal[0], "vcalendar");
+    assert_eq!(jcal[2][0][0], "vtimezone");
+    assert_eq!(jcal[2][0][2][0][0], "standard");
+    assert_eq!(jcal[2][1][2][0][0], "valarm");
+
+    // the same as reading it back from iCalendar text
+    let read = Calendar::from_jcal(&jcal).unwrap();
+    let parsed: Calendar = calendar.to_string_with(&options).parse().unwrap();
+    assert_eq!(read.to_string(), parsed.to_string());
+}
+
+#[cfg(feature = "xcal")]
+#[test]
+fn test_xcal_round_trip() {
+    let start = NaiveDate::from_ymd_opt(2021, 3, 1)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let mut calendar = Calendar::new();
+    calendar
+        .push(
+            Timezone::new("Europe/Berlin")
+                .standard(
+                    Observance::new()
+                        .starts(start)
+                        .offset_from(FixedOffset::east_opt(7200).unwrap())
+                        .offset_to(FixedOffset::east_opt(3600).unwrap())
+                        .repeats("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU".parse().unwrap())
+                        .done(),
+                )
+                .done(),
+        )
+        .push(
+            Event::new()
+                .summary("Planning & <review>")
+                .starts((start, "Europe/Berlin"))
+                .add_property("GEO", "52.52;13.40")
+                .attendee(
+                    Attendee::new("jane@example.com")
+                        .common_name("Jane Doe")
+                        .rsvp(true)
+                        .done(),
+                )
+                .add_property("CATEGORIES", "Work,Out\\, of office")
+                .done(),
+        );
+    let options = WriteOptions::deterministic(Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap());
+
+    let xcal = calendar.to_xcal_with(&options);
+    assert!(xcal.starts_with("<?xml"));
+    assert!(xcal.contains("<icalendar xmlns=\"urn:ietf:params:xml:ns:icalendar-2.0\">"));
+    assert!(xcal.contains("<latitude>52.52</latitude>"));
+    assert!(xcal.contains("<text>Planning &amp; &lt;review&gt;</text>"));
+
+    // the same as reading it back from iCalendar text
+    let read = Calendar::from_xcal(&xcal).unwrap();
+    let parsed: Calendar = calendar.to_string_with(&options).parse().unwrap();
+    assert_eq!(read.to_string(), parsed.to_string());
+}
+
+#[cfg(feature = "xcal")]
+#[test]
+fn test_xcal_keeps_whitespace() {
+    let mut calendar = Calendar::new();
+    calendar.push(
+        Event::new()
+            .summary("  padded  ")
+            .description("line\n")
+            .uid("padded@example.com")
+            .done(),
+    );
+
+    let read = Calendar::from_xcal(&calendar.to_xcal()).unwrap();
+    let event = read.events().next().unwrap();
+    assert_eq!(event.get_summary(), Some("  padded  "));
+    assert_eq!(event.properties()["DESCRIPTION"].value(), "line\n");
+    assert_eq!(event.get_uid(), Some("padded@example.com"));
+}
+
+#[test]
+fn test_typed_getters() {
+    let start = Utc.with_ymd_and_hms(2021, 3, 1, 8, 0, 0).unwrap();
+    let event = Event::new()
+        .summary("Planning")
+        .starts(start)
+        .ends((start.naive_utc(), "Europe/Berlin"))
+        .status(EventStatus::Tentative)
+        .class(Class::Private)
+        .priority(3)
+        .uid("planning")
+        .done();
+    let todo = Todo::new()
+        .percent_complete(42)
+        .due(start)
+        .status(TodoStatus::InProcess)
+        .done();
+    let journal = Journal::new().status(JournalStatus::Final).done();
+
+    // the same whether built or parsed
+    let parsed: Event = event.to_string().parse().unwrap();
+    for event in &[event, parsed] {
+        assert_eq!(event.get_summary(), Some("Planning"));
+        assert_eq!(event.get_start(), Some(start.into()));
+        assert_eq!(
+            event.get_end(),
+            Some((start.naive_utc(), "Europe/Berlin").into())
+        );
+        assert_eq!(event.get_status(), Some(EventStatus::Tentative));
+        assert_eq!(event.get_class(), Some(Class::Private));
+        assert_eq!(event.get_priority(), Some(3));
+        assert_eq!(event.get_uid(), Some("planning"));
+    }
+    let parsed: Todo = todo.to_string().parse().unwrap();
+    for todo in &[todo, parsed] {
+        assert_eq!(todo.get_percent_complete(), Some(42));
+        assert_eq!(todo.get_due(), Some(start.into()));
+        assert_eq!(todo.get_status(), Some(TodoStatus::InProcess));
+    }
+    assert_eq!(journal.get_status(), Some(JournalStatus::Final));
+
+    let empty = Event::new();
+    assert_eq!(empty.get_summary(), None);
+    assert_eq!(empty.get_start(), None);
+    assert_eq!(empty.get_status(), None);
+    let all_day = Event::new()
+        .append_property(Property::with_value("DTSTART", start.date_naive()))
+        .done();
+    assert_eq!(
+        all_day.get_start(),
+        Some(DatePerhapsTime::Date(start.date_naive()))
+    );
+    // a start that is read can be set again, staying all day
+    let copied = Event::new().starts(all_day.get_start().unwrap()).done();
+    assert!(copied
+        .to_string()
+        .contains("\r\nDTSTART;VALUE=DATE:20210301\r\n"));
+}
+
+#[test]
+fn test_mutate_properties() {
+    let start = Utc.with_ymd_and_hms(2021, 3, 1, 8, 0, 0).unwrap();
+    let mut event = Event::new()
+        .summary("Planning")
+        .starts(start)
+        .ends(start + chrono::Duration::hours(1))
+        .attendee(Attendee::new("jane@example.com").done())
+        .attendee(Attendee::new("john@example.com").done())
+        .done();
+
+    // switch from DTEND to DURATION
+    let end = event.remove_property("DTEND").unwrap();
+    assert_eq!(end.value(), "20210301T090000Z");
+    assert!(event.remove_property("DTEND").is_none());
+    event.append_property(Property::with_value("DURATION", chrono::Duration::hours(1)));
+    assert!(event.get_end().is_none());
+
+    event
+        .property_mut("SUMMARY")
+        .unwrap()
+        .set_value("Planning, moved")
+        .add_parameter("LANGUAGE", "en");
+    assert_eq!(event.get_summary(), Some("Planning, moved"));
+    assert!(event.property_mut("LOCATION").is_none());
+
+    event.retain_multi_properties(|property| {
+        !(property.key() == "ATTENDEE" && property.value() == "mailto:john@example.com")
+    });
+    assert_eq!(event.attendees().len(), 1);
+    event.multi_properties_mut().clear();
+    assert!(event.attendees().is_empty());
+
+    let written = event.to_string();
+    assert!(written.contains("DURATION:PT1H\r\n"));
+    assert!(written.contains("SUMMARY;LANGUAGE=en:Planning\\, moved\r\n"));
+    assert!(!written.contains("DTEND"));
+}
+
+#[test]
+fn test_calendar_accessors() {
+    let mut calendar = Calendar::new();
+    calendar
+        .name("Team")
+        .push(Event::new().uid("standup").summary("Standup").done())
+        .push(Todo::new().uid("agenda").summary("Agenda").done())
+        .push(Event::new().uid("retro").summary("Retro").done())
+        .push(Timezone::new("Europe/Berlin"));
+
+    let summaries: Vec<_> = calendar.events().filter_map(Event::get_summary).collect();
+    assert_eq!(summaries, ["Standup", "Retro"]);
+    assert_eq!(calendar.todos().count(), 1);
+    assert_eq!(calendar.venues().count(), 0);
+    match calendar.find_by_uid("agenda") {
+        Some(CalendarElement::Todo(todo)) => assert_eq!(todo.get_summary(), Some("Agenda")),
+        other => panic!("expected the todo, got {:?}", other),
+    }
+    assert!(calendar.find_by_uid("missing").is_none());
+
+    for component in calendar.iter_mut() {
+        if let CalendarElement::Event(event) = component {
+            event.location("Room 1");
+        }
+    }
+    assert!(calendar
+        .events()
+        .all(|event| event.properties()["LOCATION"].value() == "Room 1"));
+
+    assert_eq!(calendar.remove_by_uid("standup").len(), 1);
+    assert_eq!(calendar.len(), 3);
+    assert_eq!(calendar.properties().len(), 2);
+    assert_eq!(calendar.remove_property("NAME").unwrap().value(), "Team");
+    assert!(calendar.remove_property("NAME").is_none());
+    assert_eq!(calendar.properties()[0].key(), "X-WR-CALNAME");
+
+    for property in calendar.properties_mut() {
+        property.add_parameter("LANGUAGE", "en");
+    }
+    calendar.append_property(Property::new("METHOD", "PUBLISH"));
+    assert!(calendar
+        .to_string()
+        .contains("\r\nX-WR-CALNAME;LANGUAGE=en:Team\r\n"));
+    calendar.retain_properties(|property| property.key() != "X-WR-CALNAME");
+    assert_eq!(calendar.properties().len(), 1);
+
+    calendar
+        .append_property(Property::new("X-TAG", "a"))
+        .append_property(Property::new("X-TAG", "b"));
+    assert_eq!(calendar.remove_properties("X-TAG").len(), 2);
+    assert_eq!(calendar.properties().len(), 1);
+}
+
+#[test]
+fn test_ensure_uid() {
+    let mut event = Event::new().summary("Planning").done();
+    assert_ne!(event.to_string(), event.to_string());
+
+    event.ensure_uid().ensure_timestamp();
+    let uid = event.properties()["UID"].value().into_owned();
+    assert_eq!(event.to_string(), event.to_string());
+    assert_eq!(event.ensure_uid().properties()["UID"].value(), uid);
+
+    let parsed: Event = event.to_string().parse().unwrap();
+    assert_eq!(parsed.properties()["UID"].value(), uid);
+}
+
+#[test]
+fn test_deterministic_output() {
+    let build = || {
+        let mut calendar = Calendar::new();
+        calendar.push(
+            Event::new()
+                .summary("Planning")
+                .attendee(
+                    Attendee::new("jane@example.com")
+                        .common_name("Jane Doe")
+                        .role(AttendeeRole::Chair)
+                        .participation_status(ParticipationStatus::Accepted)
+                        .rsvp(false)
+                        .done(),
+                )
+                .done(),
+        );
+        calendar.push(Todo::new().summary("Agenda").done());
+        calendar
+    };
+    let options = WriteOptions::deterministic(Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap());
+    let ics = build().to_string_with(&options);
+    assert_eq!(ics, build().to_string_with(&options));
+    assert_ne!(build().to_string(), build().to_string());
+
+    let uids: Vec<_> = ics
+        .lines()
+        .filter(|line| line.starts_with("UID:"))
+        .collect();
+    assert_eq!(uids.len(), 2);
+    assert_ne!(uids[0], uids[1]);
+    assert!(ics.contains(
+        "BEGIN:VEVENT\r\n\
+         DTSTAMP:20210301T000000Z\r\n\
+         ATTENDEE;"
+    ));
+    assert!(ics.contains(
+        "ATTENDEE;CN=Jane Doe;ROLE=CHAIR;PARTSTAT=ACCEPTED;RSVP=FALSE:mailto:jane@ex\r\n ample.com\r\n"
+    ));
+}
+
+#[cfg(feature = "chrono-tz")]
+#[test]
+fn test_add_timezones() {
+    use icalendar::{Freq, RecurrenceRule};
+
+    let start = NaiveDate::from_ymd_opt(2005, 1, 4)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let mut calendar = Calendar::new();
+    calendar.push(
+        Event::new()
+            .starts((start, "America/New_York"))
+            .repeats(RecurrenceRule::new(Freq::Yearly).count(3).done().unwrap())
+            .done(),
+    );
+    calendar.push(Todo::new().due((start, "Europe/Berlin")).done());
+    calendar.push(Todo::new().due((start, "Not/A_Zone")).done());
+    // followed for a few years only, not until the year 2999
+    calendar.push(
+        Event::new()
+            .starts((start, "Europe/Paris"))
+            .repeats("FREQ=DAILY;UNTIL=29991231T000000Z".parse().unwrap())
+            .done(),
+    );
+    calendar.push(Timezone::new("Europe/Berlin"));
+    calendar.add_timezones();
+
+    let ics = calendar.to_string();
+    assert_eq!(ics.matches("BEGIN:VTIMEZONE").count(), 3);
+    assert!(ics.contains("TZID:Europe/Paris\r\n"));
+    assert_eq!(ics.matches("TZID:Europe/Berlin\r\n").count(), 1);
+    assert!(ics.contains("TZID:America/New_York\r\n"));
+    assert!(!ics.contains("TZID:Not/A_Zone\r\n"));
+    // the range reaches the last occurrence, right before the rules changed in 2007
+    assert!(ics.contains("RRULE:FREQ=YEARLY;UNTIL=20060402T070000Z;BYDAY=1SU;BYMONTH=4\r\n"));
+}
+
+#[cfg(feature = "chrono-tz")]
+#[test]
+fn test_free_busy_without_timezones() {
+    let local = NaiveDate::from_ymd_opt(2021, 7, 1)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+    let utc = |hour| Utc.with_ymd_and_hms(2021, 7, 1, hour, 0, 0).unwrap();
+    let mut calendar = Calendar::new();
+    calendar.push(
+        Event::new()
+            .starts((local, "America/New_York"))
+            .ends((local + chrono::Duration::hours(1), "America/New_York"))
+            .done(),
+    );
+
+    let free_busy = calendar.free_busy(utc(0), utc(23));
+    assert_eq!(
+        free_busy.periods(),
+        vec![(FreeBusyType::Busy, Period::new(utc(14), utc(15)))]
+    );
+}